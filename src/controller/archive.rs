@@ -0,0 +1,176 @@
+use std::ops::Range;
+
+use super::models::{BlockIdFull, PackageType};
+
+/// Archive package as written by the node into the `archives` column family:
+/// a package header followed by entries with a file name and data.
+pub struct ArchivePackage {
+    pub entries: Vec<ArchiveEntry>,
+    /// Offset and reason if the package is truncated or malformed.
+    pub error: Option<(usize, &'static str)>,
+}
+
+impl ArchivePackage {
+    const MAGIC: u32 = 0xae8fdd01;
+    const ENTRY_MAGIC: u16 = 0x1e8b;
+
+    const HEADER_LEN: usize = 4;
+    const ENTRY_HEADER_LEN: usize = 8;
+
+    pub fn parse(data: &[u8]) -> Self {
+        let mut result = Self {
+            entries: Vec::new(),
+            error: None,
+        };
+
+        match data.get(..Self::HEADER_LEN) {
+            Some(magic) if u32::from_le_bytes(magic.try_into().unwrap()) == Self::MAGIC => {}
+            Some(_) => {
+                result.error = Some((0, "invalid package magic"));
+                return result;
+            }
+            None => {
+                result.error = Some((0, "truncated package header"));
+                return result;
+            }
+        }
+
+        let mut offset = Self::HEADER_LEN;
+        while offset < data.len() {
+            match Self::read_entry(data, offset) {
+                Ok(entry) => {
+                    offset = entry.data.end;
+                    result.entries.push(entry);
+                }
+                Err(reason) => {
+                    result.error = Some((offset, reason));
+                    break;
+                }
+            }
+        }
+        result
+    }
+
+    fn read_entry(data: &[u8], offset: usize) -> Result<ArchiveEntry, &'static str> {
+        let header = data
+            .get(offset..offset + Self::ENTRY_HEADER_LEN)
+            .ok_or("truncated entry header")?;
+        if u16::from_le_bytes([header[0], header[1]]) != Self::ENTRY_MAGIC {
+            return Err("invalid entry magic");
+        }
+        let name_len = u16::from_le_bytes([header[2], header[3]]) as usize;
+        let data_len = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+
+        let name_range =
+            offset + Self::ENTRY_HEADER_LEN..offset + Self::ENTRY_HEADER_LEN + name_len;
+        let data_range = name_range.end..name_range.end + data_len;
+        let name = data.get(name_range.clone()).ok_or("truncated entry name")?;
+        if data_range.end > data.len() {
+            return Err("truncated entry data");
+        }
+
+        let name = String::from_utf8_lossy(name).into_owned();
+        Ok(ArchiveEntry {
+            id: parse_entry_name(&name),
+            name,
+            header: offset..name_range.start,
+            name_range,
+            data: data_range,
+        })
+    }
+}
+
+pub struct ArchiveEntry {
+    pub name: String,
+    /// Entry type and block id parsed from the name.
+    pub id: Option<(PackageType, BlockIdFull)>,
+    pub header: Range<usize>,
+    pub name_range: Range<usize>,
+    pub data: Range<usize>,
+}
+
+/// Parses names like `block_(-1,8000000000000000,123):<root hash>:<file hash>`.
+fn parse_entry_name(name: &str) -> Option<(PackageType, BlockIdFull)> {
+    let (package_type, rest) = [
+        ("prooflink_", PackageType::ProofLink),
+        ("proof_", PackageType::Proof),
+        ("block_", PackageType::Block),
+    ]
+    .into_iter()
+    .find_map(|(prefix, package_type)| Some((package_type, name.strip_prefix(prefix)?)))?;
+
+    let (short_id, hashes) = rest.strip_prefix('(')?.split_once(')')?;
+    let mut short_id = short_id.split(',');
+    let workchain = short_id.next()?.trim().parse().ok()?;
+    let shard = u64::from_str_radix(short_id.next()?.trim(), 16).ok()?;
+    let seqno = short_id.next()?.trim().parse().ok()?;
+
+    let (root_hash, file_hash) = hashes.strip_prefix(':')?.split_once(':')?;
+    let mut id = BlockIdFull {
+        workchain,
+        shard,
+        seqno,
+        root_hash: [0; 32],
+        file_hash: [0; 32],
+    };
+    hex::decode_to_slice(root_hash, &mut id.root_hash).ok()?;
+    hex::decode_to_slice(file_hash, &mut id.file_hash).ok()?;
+    Some((package_type, id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut result = ArchivePackage::ENTRY_MAGIC.to_le_bytes().to_vec();
+        result.extend((name.len() as u16).to_le_bytes());
+        result.extend((data.len() as u32).to_le_bytes());
+        result.extend(name.as_bytes());
+        result.extend(data);
+        result
+    }
+
+    #[test]
+    fn entries() {
+        let name = format!(
+            "proof_(-1,8000000000000000,123):{}:{}",
+            "11".repeat(32),
+            "22".repeat(32)
+        );
+        let mut data = ArchivePackage::MAGIC.to_le_bytes().to_vec();
+        data.extend(entry(&name, &[1, 2, 3]));
+        data.extend(entry("other", &[]));
+
+        let package = ArchivePackage::parse(&data);
+        assert!(package.error.is_none());
+        assert_eq!(package.entries.len(), 2);
+
+        let (package_type, id) = package.entries[0].id.unwrap();
+        assert_eq!(package_type, PackageType::Proof);
+        assert_eq!(
+            (id.workchain, id.shard, id.seqno),
+            (-1, 0x8000000000000000, 123)
+        );
+        assert_eq!((id.root_hash, id.file_hash), ([0x11; 32], [0x22; 32]));
+        assert_eq!(&data[package.entries[0].data.clone()], [1, 2, 3]);
+
+        assert_eq!(package.entries[1].name, "other");
+        assert!(package.entries[1].id.is_none());
+    }
+
+    #[test]
+    fn truncated() {
+        let mut data = ArchivePackage::MAGIC.to_le_bytes().to_vec();
+        data.extend(entry("block", &[1, 2, 3]));
+        data.pop();
+
+        let package = ArchivePackage::parse(&data);
+        assert!(package.entries.is_empty());
+        assert_eq!(package.error, Some((4, "truncated entry data")));
+        assert_eq!(
+            ArchivePackage::parse(&[1, 2, 3, 4]).error,
+            Some((0, "invalid package magic"))
+        );
+    }
+}
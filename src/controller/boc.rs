@@ -0,0 +1,551 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+
+use super::value::{Value, ValueKind};
+
+/// Bag of cells, the serialization format of blocks, proofs and states.
+pub struct Boc {
+    pub header: BocHeader,
+    pub cells: Vec<Cell>,
+    pub roots: Vec<usize>,
+    /// Stored checksum and whether it matches the data.
+    pub crc: Option<(u32, bool)>,
+    by_hash: HashMap<[u8; 32], usize>,
+}
+
+pub struct BocHeader {
+    pub has_index: bool,
+    pub cell_count: usize,
+    pub root_count: usize,
+    pub absent_count: usize,
+    /// Length of the header including the root list and the index.
+    pub len: usize,
+}
+
+impl Boc {
+    const GENERIC_MAGIC: u32 = 0xb5ee9c72;
+    const INDEXED_MAGIC: u32 = 0x68ff65f3;
+    const INDEXED_CRC_MAGIC: u32 = 0xacc3a728;
+
+    /// Parses and hashes all cells, returns the offset and reason on failure.
+    pub fn parse(data: &[u8]) -> Result<Self, (usize, &'static str)> {
+        let mut reader = Reader::new(data);
+
+        let magic = reader.uint(4)? as u32;
+        let flags = reader.uint(1)? as u8;
+        let (has_index, has_crc, ref_size) = match magic {
+            Self::GENERIC_MAGIC => (
+                flags & 0x80 != 0,
+                flags & 0x40 != 0,
+                (flags & 0x07) as usize,
+            ),
+            Self::INDEXED_MAGIC => (true, false, flags as usize),
+            Self::INDEXED_CRC_MAGIC => (true, true, flags as usize),
+            _ => return Err((0, "invalid BOC magic")),
+        };
+        if !(1..=4).contains(&ref_size) {
+            return Err((4, "invalid ref size"));
+        }
+
+        let offset_size = reader.uint(1)?;
+        if !(1..=8).contains(&offset_size) {
+            return Err((5, "invalid offset size"));
+        }
+
+        let cell_count = reader.uint(ref_size)?;
+        let root_count = reader.uint(ref_size)?;
+        let absent_count = reader.uint(ref_size)?;
+        let total_cells_size = reader.uint(offset_size)?;
+
+        // Every cell takes at least two bytes
+        if cell_count > data.len() / 2 || root_count == 0 || root_count > cell_count {
+            return Err((6, "invalid cell count"));
+        }
+        if absent_count > cell_count {
+            return Err((6 + ref_size * 2, "invalid absent cell count"));
+        }
+
+        let roots = if magic == Self::GENERIC_MAGIC {
+            (0..root_count)
+                .map(|_| match reader.uint(ref_size)? {
+                    root if root < cell_count => Ok(root),
+                    _ => Err((reader.offset - ref_size, "invalid root index")),
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        } else if root_count == 1 {
+            vec![0]
+        } else {
+            return Err((6 + ref_size, "indexed BOC must have one root"));
+        };
+
+        if has_index {
+            reader.read(cell_count * offset_size)?;
+        }
+
+        let cells_start = reader.offset;
+        let mut cells = Vec::with_capacity(cell_count);
+        for index in 0..cell_count {
+            cells.push(Cell::read(&mut reader, index, cell_count, ref_size)?);
+        }
+        if reader.offset - cells_start != total_cells_size {
+            return Err((cells_start, "cell data size mismatch"));
+        }
+
+        let crc = if has_crc {
+            let checked = &data[..reader.offset];
+            let stored = u32::from_le_bytes(reader.read(4)?.try_into().unwrap());
+            Some((stored, crc32c::crc32c(checked) == stored))
+        } else {
+            None
+        };
+        if reader.offset != data.len() {
+            return Err((reader.offset, "trailing data"));
+        }
+
+        // References always point to the following cells
+        for index in (0..cells.len()).rev() {
+            let (hashes, depths) = cells[index].compute_hashes(&cells)?;
+            let cell = &mut cells[index];
+            cell.hashes = hashes;
+            cell.depths = depths;
+        }
+
+        let by_hash = cells
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| (cell.repr_hash(), index))
+            .collect();
+
+        Ok(Self {
+            header: BocHeader {
+                has_index,
+                cell_count,
+                root_count,
+                absent_count,
+                len: cells_start,
+            },
+            cells,
+            roots,
+            crc,
+            by_hash,
+        })
+    }
+
+    pub fn root_hash(&self) -> [u8; 32] {
+        self.cells[self.roots[0]].repr_hash()
+    }
+
+    /// Hash of the cell proven by the merkle proof at the root or in its references,
+    /// which is the root hash of the block for block proofs.
+    pub fn proven_root_hash(&self) -> Option<[u8; 32]> {
+        let root = &self.cells[self.roots[0]];
+        std::iter::once(root)
+            .chain(root.refs.iter().map(|&child| &self.cells[child]))
+            .find(|cell| cell.cell_type == CellType::MerkleProof)
+            .and_then(|cell| cell.data.get(1..33)?.try_into().ok())
+    }
+
+    pub fn root_hashes(&self) -> Vec<[u8; 32]> {
+        self.roots
+            .iter()
+            .map(|&root| self.cells[root].repr_hash())
+            .collect()
+    }
+
+    /// Decoded header of the BOC parsed from `data`.
+    pub fn summary(&self, data: &[u8]) -> Value {
+        let header = 0..self.header.len;
+        let count = |count: usize| Value::new(header.clone(), ValueKind::Uint(count as u64));
+        let crc = match self.crc {
+            Some((_, true)) => "ok",
+            Some((_, false)) => "mismatch",
+            None => "absent",
+        };
+        let crc_range = match self.crc {
+            Some(_) => data.len() - 4..data.len(),
+            None => data.len()..data.len(),
+        };
+        let root = &self.cells[self.roots[0]];
+
+        Value::new(
+            0..data.len(),
+            ValueKind::Struct(vec![
+                ("cells", count(self.header.cell_count)),
+                ("roots", count(self.header.root_count)),
+                ("absent", count(self.header.absent_count)),
+                (
+                    "has_index",
+                    Value::new(
+                        header.clone(),
+                        ValueKind::Str(self.header.has_index.to_string()),
+                    ),
+                ),
+                ("crc", Value::new(crc_range, ValueKind::Str(crc.to_owned()))),
+                (
+                    "root_hash",
+                    Value::new(
+                        root.range.clone(),
+                        ValueKind::Bytes(self.root_hash().to_vec()),
+                    ),
+                ),
+            ]),
+        )
+    }
+}
+
+impl CellSource for Boc {
+    fn load_cell(&self, hash: &[u8; 32]) -> Result<CellInfo> {
+        let index = *self.by_hash.get(hash).context("Cell not found")?;
+        let cell = &self.cells[index];
+        Ok(CellInfo {
+            cell_type: cell.cell_type,
+            level_mask: cell.level_mask,
+            bit_len: cell.bit_len,
+            data: cell.data.clone(),
+            hash: cell.repr_hash(),
+            depth: cell.repr_depth(),
+            refs: cell
+                .refs
+                .iter()
+                .map(|&index| self.cells[index].repr_hash())
+                .collect(),
+        })
+    }
+}
+
+pub struct Cell {
+    pub range: Range<usize>,
+    pub cell_type: CellType,
+    pub level_mask: u8,
+    pub bit_len: usize,
+    /// Data bytes with the completion tag.
+    pub data: Vec<u8>,
+    pub refs: Vec<usize>,
+    hashes: Vec<[u8; 32]>,
+    depths: Vec<u16>,
+}
+
+impl Cell {
+    fn read(
+        reader: &mut Reader<'_>,
+        index: usize,
+        cell_count: usize,
+        ref_size: usize,
+    ) -> Result<Self, (usize, &'static str)> {
+        let start = reader.offset;
+        let d1 = reader.uint(1)? as u8;
+        let d2 = reader.uint(1)?;
+
+        let ref_count = (d1 & 0x07) as usize;
+        if ref_count > 4 {
+            return Err((start, "absent cells are not supported"));
+        }
+        let is_exotic = d1 & 0x08 != 0;
+        let level_mask = d1 >> 5;
+
+        // Stored hashes are computed again anyway
+        if d1 & 0x10 != 0 {
+            let hash_count = level_mask.count_ones() as usize + 1;
+            reader.read(hash_count * (32 + 2))?;
+        }
+
+        let data_len = d2.div_ceil(2);
+        let data = reader.read(data_len)?.to_vec();
+        let bit_len = if d2.is_multiple_of(2) {
+            data_len * 8
+        } else {
+            match data.last() {
+                Some(&last) if last != 0 => data_len * 8 - 1 - last.trailing_zeros() as usize,
+                _ => return Err((reader.offset - 1, "invalid completion tag")),
+            }
+        };
+
+        let mut refs = Vec::with_capacity(ref_count);
+        for _ in 0..ref_count {
+            match reader.uint(ref_size)? {
+                child if child > index && child < cell_count => refs.push(child),
+                _ => return Err((reader.offset - ref_size, "invalid cell reference")),
+            }
+        }
+
+        let Some(cell_type) = CellType::from_data(is_exotic, &data) else {
+            return Err((start + 2, "unknown exotic cell type"));
+        };
+
+        // Pruned branches keep the hashes and depths of the lower levels in the data
+        let stored_count = level_mask.count_ones() as usize;
+        if cell_type == CellType::PrunedBranch && data.len() < 2 + stored_count * (32 + 2) {
+            return Err((start, "pruned branch is too short"));
+        }
+
+        Ok(Self {
+            range: start..reader.offset,
+            cell_type,
+            level_mask,
+            bit_len,
+            data,
+            refs,
+            hashes: Vec::new(),
+            depths: Vec::new(),
+        })
+    }
+
+    /// Representation hash, the one used to reference the cell.
+    pub fn repr_hash(&self) -> [u8; 32] {
+        *self.hashes.last().unwrap()
+    }
+
+    pub fn repr_depth(&self) -> u16 {
+        *self.depths.last().unwrap()
+    }
+
+    fn hash(&self, level: u8) -> [u8; 32] {
+        self.hashes[hash_index(self.level_mask, level)]
+    }
+
+    fn depth(&self, level: u8) -> u16 {
+        self.depths[hash_index(self.level_mask, level)]
+    }
+
+    /// Computes the hashes for every significant level, children must be hashed already.
+    fn compute_hashes(&self, cells: &[Cell]) -> Result<LevelHashes, (usize, &'static str)> {
+        let level = 8 - self.level_mask.leading_zeros() as u8;
+        let child_level_shift = match self.cell_type {
+            CellType::MerkleProof | CellType::MerkleUpdate => 1,
+            _ => 0,
+        };
+
+        let mut hashes = Vec::new();
+        let mut depths = Vec::new();
+        if self.cell_type == CellType::PrunedBranch {
+            let count = self.level_mask.count_ones() as usize;
+            let depths_start = 2 + count * 32;
+            for i in 0..count {
+                hashes.push(self.data[2 + i * 32..2 + (i + 1) * 32].try_into().unwrap());
+                let depth = &self.data[depths_start + i * 2..depths_start + (i + 1) * 2];
+                depths.push(u16::from_be_bytes(depth.try_into().unwrap()));
+            }
+        }
+        let first_computed = hashes.len();
+
+        for level_i in 0..=level {
+            let significant = level_i == 0 || self.level_mask & (1 << (level_i - 1)) != 0;
+            if !significant || hash_index(self.level_mask, level_i) < first_computed {
+                continue;
+            }
+
+            let level_mask = self.level_mask & ((1 << level_i) - 1);
+            let d1 = self.refs.len() as u8
+                | (u8::from(self.cell_type != CellType::Ordinary) << 3)
+                | (level_mask << 5);
+            let d2 = (self.bit_len / 8 + self.bit_len.div_ceil(8)) as u8;
+
+            let mut hasher = Sha256::new();
+            hasher.update([d1, d2]);
+            match hashes.last() {
+                Some(prev) if hashes.len() > first_computed => hasher.update(prev),
+                _ => hasher.update(&self.data),
+            }
+
+            let child_level = level_i + child_level_shift;
+            let mut depth = 0;
+            for &child in &self.refs {
+                let child_depth = cells[child].depth(child_level);
+                hasher.update(child_depth.to_be_bytes());
+                // Pruned branches can store any depth
+                let Some(next_depth) = child_depth.checked_add(1) else {
+                    return Err((self.range.start, "cell depth overflow"));
+                };
+                depth = depth.max(next_depth);
+            }
+            for &child in &self.refs {
+                hasher.update(cells[child].hash(child_level));
+            }
+
+            hashes.push(hasher.finalize().into());
+            depths.push(depth);
+        }
+        Ok((hashes, depths))
+    }
+}
+
+/// Hashes and depths of the significant levels.
+type LevelHashes = (Vec<[u8; 32]>, Vec<u16>);
+
+/// Index of the hash used for `level` in a cell with the level mask.
+fn hash_index(level_mask: u8, level: u8) -> usize {
+    (level_mask & ((1u8 << level.min(3)) - 1)).count_ones() as usize
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellType {
+    Ordinary,
+    PrunedBranch,
+    LibraryReference,
+    MerkleProof,
+    MerkleUpdate,
+}
+
+impl CellType {
+    /// Exotic cells store their type in the first data byte.
+    pub(super) fn from_data(is_exotic: bool, data: &[u8]) -> Option<Self> {
+        match (is_exotic, data.first()) {
+            (false, _) => Some(Self::Ordinary),
+            (true, Some(1)) => Some(Self::PrunedBranch),
+            (true, Some(2)) => Some(Self::LibraryReference),
+            (true, Some(3)) => Some(Self::MerkleProof),
+            (true, Some(4)) => Some(Self::MerkleUpdate),
+            (true, _) => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ordinary => "ordinary",
+            Self::PrunedBranch => "pruned branch",
+            Self::LibraryReference => "library reference",
+            Self::MerkleProof => "merkle proof",
+            Self::MerkleUpdate => "merkle update",
+        }
+    }
+}
+
+/// Cell loaded from a BOC or from the cell storage.
+pub struct CellInfo {
+    pub cell_type: CellType,
+    pub level_mask: u8,
+    pub bit_len: usize,
+    pub data: Vec<u8>,
+    pub hash: [u8; 32],
+    pub depth: u16,
+    pub refs: Vec<[u8; 32]>,
+}
+
+impl CellInfo {
+    /// Data bits in hex, with `_` marking the completion tag as in `x{A5_}`.
+    pub fn data_hex(&self) -> String {
+        let nibbles = self.bit_len.div_ceil(4);
+        let mut result = hex::encode(&self.data);
+        result.truncate(nibbles);
+        if !self.bit_len.is_multiple_of(4) {
+            result.push('_');
+        }
+        result
+    }
+}
+
+/// Cells addressed by their representation hash.
+pub trait CellSource {
+    fn load_cell(&self, hash: &[u8; 32]) -> Result<CellInfo>;
+}
+
+pub(super) struct Reader<'a> {
+    data: &'a [u8],
+    pub(super) offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(super) fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    pub(super) fn read(&mut self, len: usize) -> Result<&'a [u8], (usize, &'static str)> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len());
+        let Some(end) = end else {
+            return Err((self.offset, "unexpected end of data"));
+        };
+        let result = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(result)
+    }
+
+    /// Reads a big-endian integer of up to 8 bytes.
+    pub(super) fn uint(&mut self, len: usize) -> Result<usize, (usize, &'static str)> {
+        let bytes = self.read(len)?;
+        Ok(bytes.iter().fold(0u64, |acc, &byte| acc << 8 | byte as u64) as usize)
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    /// Serializes cells into a BOC without index and CRC, with the first cell as the root.
+    pub(crate) fn boc(cells: &[Vec<u8>]) -> Vec<u8> {
+        let cells_len = cells.iter().map(Vec::len).sum::<usize>();
+        let mut result = vec![0xb5, 0xee, 0x9c, 0x72, 0x01, 0x02];
+        result.extend([cells.len() as u8, 1, 0]);
+        result.extend((cells_len as u16).to_be_bytes());
+        result.push(0);
+        for cell in cells {
+            result.extend(cell);
+        }
+        result
+    }
+
+    /// Ordinary cell with data bits given as `0` and `1`, other chars are ignored.
+    pub(crate) fn cell(bits: &str, refs: &[u8]) -> Vec<u8> {
+        let bits = bits
+            .chars()
+            .filter_map(|c| match c {
+                '0' => Some(false),
+                '1' => Some(true),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut data = vec![0u8; bits.len() / 8 + 1];
+        for (i, bit) in bits.iter().enumerate() {
+            data[i / 8] |= u8::from(*bit) << (7 - i % 8);
+        }
+        if bits.len() % 8 == 0 {
+            data.pop();
+        } else {
+            // Completion tag
+            data[bits.len() / 8] |= 0x80 >> (bits.len() % 8);
+        }
+
+        let mut result = vec![
+            refs.len() as u8,
+            (bits.len() / 8 + bits.len().div_ceil(8)) as u8,
+        ];
+        result.extend(data);
+        result.extend(refs);
+        result
+    }
+
+    #[test]
+    fn empty_cell_hash() {
+        let boc = Boc::parse(&boc(&[cell("", &[])])).unwrap();
+        assert_eq!(
+            hex::encode(boc.root_hash()),
+            "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7"
+        );
+        assert_eq!(boc.cells[0].repr_depth(), 0);
+    }
+
+    #[test]
+    fn child_depth() {
+        let boc = Boc::parse(&boc(&[cell("1", &[1]), cell("01", &[2]), cell("", &[])])).unwrap();
+        assert_eq!(boc.cells[0].repr_depth(), 2);
+        assert_eq!(boc.cells[0].bit_len, 1);
+        assert_eq!(boc.cells[1].bit_len, 2);
+    }
+
+    #[test]
+    fn depth_overflow() {
+        // Pruned branch of level 1 with the maximum stored depth
+        let mut pruned = vec![0x28, 72, 1, 1];
+        pruned.extend([0; 32]);
+        pruned.extend(u16::MAX.to_be_bytes());
+
+        let data = boc(&[cell("", &[1]), pruned]);
+        assert!(matches!(Boc::parse(&data), Err((_, "cell depth overflow"))));
+    }
+}
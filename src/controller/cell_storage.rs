@@ -0,0 +1,232 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+use super::boc::{CellInfo, CellSource, CellType, Reader};
+
+/// Column family with the cells of all stored shard states.
+pub const CELLS_CF: &str = "cells";
+
+/// Cell as stored by the node, keyed by its representation hash:
+/// `refcount: i64 LE, d1, d2, bit_len: u16 LE, data, (hash, depth: u16 LE)*, ref hash*`.
+///
+/// Cells are written once for all states that share them, the reference
+/// count drops to zero when the last such state is removed.
+pub struct StoredCell<'a> {
+    value: &'a [u8],
+    pub refcount: i64,
+    pub cell_type: CellType,
+    pub level_mask: u8,
+    pub bit_len: usize,
+    pub data: Range<usize>,
+    /// Hash and depth for each level, the last one is the representation hash.
+    pub hashes: Vec<Range<usize>>,
+    pub refs: Vec<Range<usize>>,
+}
+
+impl<'a> StoredCell<'a> {
+    /// Returns the offset and reason on failure.
+    pub fn parse(value: &'a [u8]) -> Result<Self, (usize, &'static str)> {
+        let mut reader = Reader::new(value);
+
+        let refcount = i64::from_le_bytes(reader.read(8)?.try_into().unwrap());
+        let descriptor = reader.read(2)?;
+        let (d1, d2) = (descriptor[0], descriptor[1] as usize);
+        let bit_len = u16::from_le_bytes(reader.read(2)?.try_into().unwrap()) as usize;
+        if d2 != bit_len / 8 + bit_len.div_ceil(8) {
+            return Err((10, "bit length doesn't match the descriptor"));
+        }
+
+        let ref_count = (d1 & 0x07) as usize;
+        if ref_count > 4 {
+            return Err((8, "invalid reference count"));
+        }
+        let is_exotic = d1 & 0x08 != 0;
+        let level_mask = d1 >> 5;
+
+        let data_start = reader.offset;
+        let data = reader.read(bit_len.div_ceil(8))?;
+        let Some(cell_type) = CellType::from_data(is_exotic, data) else {
+            return Err((data_start, "unknown exotic cell type"));
+        };
+
+        // Pruned branches keep the lower hashes in the data
+        let hash_count = match cell_type {
+            CellType::PrunedBranch => 1,
+            _ => level_mask.count_ones() as usize + 1,
+        };
+        let mut read_ranges = |count: usize, len: usize| {
+            (0..count)
+                .map(|_| {
+                    let start = reader.offset;
+                    reader.read(len)?;
+                    Ok(start..reader.offset)
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let hashes = read_ranges(hash_count, 32 + 2)?;
+        let refs = read_ranges(ref_count, 32)?;
+
+        if reader.offset != value.len() {
+            return Err((reader.offset, "unexpected trailing data"));
+        }
+
+        Ok(Self {
+            value,
+            refcount,
+            cell_type,
+            level_mask,
+            bit_len,
+            data: data_start..data_start + data.len(),
+            hashes,
+            refs,
+        })
+    }
+
+    pub fn repr_hash(&self) -> [u8; 32] {
+        let range = self.hashes.last().unwrap();
+        self.value[range.start..range.start + 32]
+            .try_into()
+            .unwrap()
+    }
+
+    pub fn repr_depth(&self) -> u16 {
+        let range = self.hashes.last().unwrap();
+        u16::from_le_bytes(self.value[range.start + 32..range.end].try_into().unwrap())
+    }
+
+    pub fn ref_hashes(&self) -> Vec<[u8; 32]> {
+        self.refs
+            .iter()
+            .map(|range| self.value[range.clone()].try_into().unwrap())
+            .collect()
+    }
+
+    pub fn to_info(&self) -> CellInfo {
+        CellInfo {
+            cell_type: self.cell_type,
+            level_mask: self.level_mask,
+            bit_len: self.bit_len,
+            data: self.value[self.data.clone()].to_vec(),
+            hash: self.repr_hash(),
+            depth: self.repr_depth(),
+            refs: self.ref_hashes(),
+        }
+    }
+}
+
+/// Loads cells from [`CELLS_CF`] on demand.
+pub struct CellStorage {
+    db: Arc<rocksdb::DB>,
+}
+
+impl CellStorage {
+    /// Returns `None` if the DB has no cell storage.
+    pub(super) fn new(db: Arc<rocksdb::DB>) -> Option<Self> {
+        db.cf_handle(CELLS_CF)?;
+        Some(Self { db })
+    }
+}
+
+impl CellSource for CellStorage {
+    fn load_cell(&self, hash: &[u8; 32]) -> Result<CellInfo> {
+        let handle = self
+            .db
+            .cf_handle(CELLS_CF)
+            .context("Column family not found")?;
+        let value = self
+            .db
+            .get_pinned_cf(&handle, hash)
+            .context("Failed to read cell")?
+            .context("Cell not found")?;
+
+        let cell = StoredCell::parse(&value).map_err(|(offset, reason)| {
+            anyhow::anyhow!("Invalid cell at byte {offset}: {reason}")
+        })?;
+        Ok(cell.to_info())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored_cell(d1: u8, bit_len: u16, data: &[u8], hashes: usize, refs: usize) -> Vec<u8> {
+        let d2 = bit_len / 8 + bit_len.div_ceil(8);
+        let mut result = 2i64.to_le_bytes().to_vec();
+        result.extend([d1, d2 as u8]);
+        result.extend(bit_len.to_le_bytes());
+        result.extend(data);
+        for i in 0..hashes {
+            result.extend([0x30 + i as u8; 32]);
+            result.extend((5 + i as u16).to_le_bytes());
+        }
+        for i in 0..refs {
+            result.extend([0x40 + i as u8; 32]);
+        }
+        result
+    }
+
+    #[test]
+    fn ordinary() {
+        let value = stored_cell(0x02, 12, &[0xab, 0xc8], 1, 2);
+        let cell = StoredCell::parse(&value).ok().unwrap();
+        assert_eq!(cell.refcount, 2);
+        assert_eq!(cell.cell_type, CellType::Ordinary);
+        assert_eq!((cell.level_mask, cell.bit_len), (0, 12));
+        assert_eq!(&value[cell.data.clone()], [0xab, 0xc8]);
+        assert_eq!((cell.repr_hash(), cell.repr_depth()), ([0x30; 32], 5));
+        assert_eq!(cell.ref_hashes(), [[0x40; 32], [0x41; 32]]);
+    }
+
+    #[test]
+    fn pruned_branch() {
+        // Type, level mask, then the lower hash and depth
+        let mut data = vec![1, 1];
+        data.extend([0x11; 32]);
+        data.extend(7u16.to_be_bytes());
+        let value = stored_cell(0x28, 288, &data, 1, 0);
+
+        let cell = StoredCell::parse(&value).ok().unwrap();
+        assert_eq!(cell.cell_type, CellType::PrunedBranch);
+        assert_eq!(cell.level_mask, 1);
+        assert_eq!(cell.hashes.len(), 1);
+        assert_eq!((cell.repr_hash(), cell.repr_depth()), ([0x30; 32], 5));
+        assert!(cell.refs.is_empty());
+    }
+
+    #[test]
+    fn invalid() {
+        let mut value = stored_cell(0x00, 8, &[0xff], 1, 0);
+        value[9] = 3;
+        assert_eq!(
+            StoredCell::parse(&value).err(),
+            Some((10, "bit length doesn't match the descriptor"))
+        );
+
+        let value = stored_cell(0x05, 8, &[0xff], 1, 0);
+        assert_eq!(
+            StoredCell::parse(&value).err(),
+            Some((8, "invalid reference count"))
+        );
+
+        let value = stored_cell(0x08, 8, &[0x07], 1, 0);
+        assert_eq!(
+            StoredCell::parse(&value).err(),
+            Some((12, "unknown exotic cell type"))
+        );
+
+        let mut value = stored_cell(0x01, 8, &[0xff], 1, 1);
+        value.push(0);
+        assert_eq!(
+            StoredCell::parse(&value).err(),
+            Some((79, "unexpected trailing data"))
+        );
+        value.truncate(60);
+        assert_eq!(
+            StoredCell::parse(&value).err(),
+            Some((47, "unexpected end of data"))
+        );
+    }
+}
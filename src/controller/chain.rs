@@ -0,0 +1,286 @@
+use std::collections::{HashMap, VecDeque};
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+use super::explorer;
+use super::integrity::{IntegrityEvent, IntegrityIssue};
+use super::models::{BlockIdFull, PackageEntryId};
+use super::task::{scan_cf, Task};
+
+/// Column families with the links of a block, keyed by its root hash.
+const LINK_CFS: [&str; 4] = ["prev1", "prev2", "next1", "next2"];
+
+/// Column families which must link back to the block of each link.
+pub(super) const INVERSE_LINK_CFS: [(&str, [&str; 2]); 4] = [
+    ("prev1", ["next1", "next2"]),
+    ("prev2", ["next1", "next2"]),
+    ("next1", ["prev1", "prev2"]),
+    ("next2", ["prev1", "prev2"]),
+];
+
+/// Limits the graph of busy shards with many splits and merges.
+const MAX_NODES: usize = 2000;
+
+/// Blocks around one block, connected by their prev/next links.
+pub struct ChainGraph {
+    /// The first node is the block the graph is built around.
+    pub nodes: Vec<ChainNode>,
+    pub edges: Vec<ChainEdge>,
+}
+
+pub struct ChainNode {
+    pub id: BlockIdFull,
+    /// Whether the block has no rows and is only known from the links of other blocks.
+    pub missing: bool,
+}
+
+/// Link from an older block to a newer one.
+pub struct ChainEdge {
+    pub older: usize,
+    pub newer: usize,
+    /// Whether the newer block has the older one in `prev1` or `prev2`.
+    pub has_prev: bool,
+    /// Whether the older block has the newer one in `next1` or `next2`.
+    pub has_next: bool,
+    /// Whether either block is missing, so only one direction can be stored.
+    pub missing: bool,
+}
+
+impl ChainEdge {
+    /// Both directions of a link are written together, so a missing one means a broken DB.
+    pub fn is_complete(&self) -> bool {
+        self.has_prev && self.has_next
+    }
+}
+
+/// Resolves the block id and builds the graph around it on a new thread,
+/// stopping when `task` is cancelled.
+pub(super) fn spawn_build<F>(
+    db: Arc<rocksdb::DB>,
+    text: String,
+    depth: u32,
+    task: Task,
+    on_built: F,
+) where
+    F: FnOnce(Result<ChainGraph>) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let res = explorer::resolve_block_id(&db, &text)
+            .and_then(|center| build(&db, &center, depth, &task));
+        on_built(res);
+    });
+}
+
+/// Walks the links up to `depth` steps away from `center` in any direction,
+/// so sibling shards after a split are included too.
+fn build(db: &rocksdb::DB, center: &BlockIdFull, depth: u32, task: &Task) -> Result<ChainGraph> {
+    let handles = LINK_CFS.map(|cf_name| db.cf_handle(cf_name));
+
+    let mut nodes = vec![*center];
+    let mut indices = HashMap::from([(*center, 0)]);
+    let mut edges = HashMap::<(usize, usize), ChainEdge>::new();
+
+    let mut queue = VecDeque::from([(0, 0)]);
+    while let Some((index, distance)) = queue.pop_front() {
+        if task.is_cancelled() {
+            break;
+        }
+
+        let root_hash = nodes[index].root_hash;
+        for (cf_name, handle) in LINK_CFS.iter().zip(&handles) {
+            let Some(handle) = handle else {
+                continue;
+            };
+            let value = db
+                .get_pinned_cf(handle, root_hash)
+                .with_context(|| format!("Failed to read {cf_name}"))?;
+            let Some(linked) = value.as_deref().and_then(BlockIdFull::read) else {
+                continue;
+            };
+
+            // Nodes at the last step only complete the links between known nodes
+            let linked = match indices.get(&linked) {
+                Some(&linked) => linked,
+                None if distance < depth && nodes.len() < MAX_NODES => {
+                    nodes.push(linked);
+                    indices.insert(linked, nodes.len() - 1);
+                    queue.push_back((nodes.len() - 1, distance + 1));
+                    nodes.len() - 1
+                }
+                None => continue,
+            };
+
+            let is_prev = cf_name.starts_with("prev");
+            let (older, newer) = if is_prev {
+                (linked, index)
+            } else {
+                (index, linked)
+            };
+            let edge = edges.entry((older, newer)).or_insert(ChainEdge {
+                older,
+                newer,
+                has_prev: false,
+                has_next: false,
+                missing: false,
+            });
+            if is_prev {
+                edge.has_prev = true;
+            } else {
+                edge.has_next = true;
+            }
+        }
+    }
+
+    // Link targets without rows are kept as placeholders
+    let row_handles = handles
+        .into_iter()
+        .chain([db.cf_handle("block_handles")])
+        .flatten()
+        .collect::<Vec<_>>();
+    let nodes = nodes
+        .into_iter()
+        .map(|id| {
+            let missing = !has_rows(db, &row_handles, &id)?;
+            Ok(ChainNode { id, missing })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut edges = edges.into_values().collect::<Vec<_>>();
+    for edge in &mut edges {
+        edge.missing = nodes[edge.older].missing || nodes[edge.newer].missing;
+    }
+    edges.sort_by_key(|edge| (edge.older, edge.newer));
+    Ok(ChainGraph { nodes, edges })
+}
+
+/// Whether any of the column families keyed by the block root hash has a row of the block.
+fn has_rows(
+    db: &rocksdb::DB,
+    handles: &[Arc<rocksdb::BoundColumnFamily<'_>>],
+    id: &BlockIdFull,
+) -> Result<bool> {
+    for handle in handles {
+        let value = db
+            .get_pinned_cf(handle, id.root_hash)
+            .context("Failed to read block")?;
+        if value.is_some() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Reports links whose inverse is missing or points elsewhere, and blocks
+/// referenced by `prev1`/`prev2` without `package_entries` rows.
+pub(super) fn check_links<F>(
+    db: &rocksdb::DB,
+    task: &Task,
+    on_event: &mut F,
+) -> Result<IntegrityEvent>
+where
+    F: FnMut(IntegrityEvent),
+{
+    let links = INVERSE_LINK_CFS
+        .into_iter()
+        .filter(|(cf_name, _)| db.cf_handle(cf_name).is_some())
+        .collect::<Vec<_>>();
+    let package_entries = db.cf_handle("package_entries");
+
+    let mut scanned = 0;
+    for (i, (cf_name, inverse_cf_names)) in links.iter().copied().enumerate() {
+        if task.is_cancelled() {
+            break;
+        }
+
+        let handle = db.cf_handle(cf_name).context("Column family not found")?;
+        let inverse_handles = inverse_cf_names.map(|cf_name| db.cf_handle(cf_name));
+        // Checking only the previous blocks visits each block once, except around splits
+        let package_entries = package_entries
+            .as_ref()
+            .filter(|_| cf_name.starts_with("prev"));
+
+        let cf_scanned = scan_cf(db, &handle, task, |key, value, position| {
+            let mut report = |entry: String, problem: String| {
+                on_event(IntegrityEvent::Issue(Box::new(IntegrityIssue {
+                    cf_name,
+                    key: key.to_vec(),
+                    entry,
+                    problem,
+                })));
+            };
+            let Some(linked) = BlockIdFull::read(value) else {
+                report(hex::encode(value), "invalid block id".to_owned());
+                return Ok(ControlFlow::Continue(()));
+            };
+
+            let mut inverse = Vec::new();
+            for (inverse_cf_name, inverse_handle) in inverse_cf_names.iter().zip(&inverse_handles) {
+                let Some(inverse_handle) = inverse_handle else {
+                    continue;
+                };
+                let value = db
+                    .get_pinned_cf(inverse_handle, linked.root_hash)
+                    .with_context(|| format!("Failed to read {inverse_cf_name}"))?;
+                if let Some(value) = value {
+                    inverse.push((*inverse_cf_name, BlockIdFull::read(&value)));
+                }
+            }
+
+            let links_back = inverse
+                .iter()
+                .any(|(_, id)| id.is_some_and(|id| id.root_hash[..] == key[..]));
+            if inverse.is_empty() {
+                report(
+                    linked.to_string(),
+                    format!(
+                        "dangling: linked block has no {}",
+                        inverse_cf_names.join(" or ")
+                    ),
+                );
+            } else if !links_back {
+                let targets = inverse
+                    .iter()
+                    .map(|(cf_name, id)| match id {
+                        Some(id) => format!("{cf_name} is {}", id.short()),
+                        None => format!("{cf_name} is invalid"),
+                    })
+                    .collect::<Vec<_>>();
+                report(
+                    linked.to_string(),
+                    format!("asymmetric: linked block {}", targets.join(", ")),
+                );
+            }
+
+            if let Some(package_entries) = package_entries {
+                let prefix = PackageEntryId::block_prefix(&linked);
+                let mode = rocksdb::IteratorMode::From(&prefix, rocksdb::Direction::Forward);
+                let stored = match db.iterator_cf(package_entries, mode).next() {
+                    Some(item) => item
+                        .context("Failed to read package entry")?
+                        .0
+                        .starts_with(&prefix),
+                    None => false,
+                };
+                if !stored {
+                    report(
+                        linked.to_string(),
+                        "linked block has no package_entries rows".to_owned(),
+                    );
+                }
+            }
+
+            if position.report_due {
+                on_event(IntegrityEvent::Progress {
+                    scanned: scanned + position.scanned,
+                    position: (i as f64 + position.fraction) / links.len() as f64,
+                });
+            }
+            Ok(ControlFlow::Continue(()))
+        })?;
+        scanned += cf_scanned;
+    }
+
+    Ok(IntegrityEvent::Finished { scanned })
+}
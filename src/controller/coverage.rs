@@ -0,0 +1,123 @@
+use std::ops::{ControlFlow, RangeInclusive};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+use super::archive::ArchivePackage;
+use super::task::{scan_cf, Task};
+
+pub enum CoverageEvent {
+    /// Position is the estimated fraction of all archives already scanned.
+    Archive {
+        archive: Box<ArchiveCoverage>,
+        position: f64,
+    },
+    Finished {
+        scanned: usize,
+    },
+    Failed(anyhow::Error),
+}
+
+/// Masterchain blocks stored in one archive package.
+pub struct ArchiveCoverage {
+    pub key: Vec<u8>,
+    pub archive_id: u32,
+    /// Lowest and highest seqno of the masterchain entries.
+    pub mc_seqnos: Option<RangeInclusive<u32>>,
+    pub size: usize,
+    pub entries: usize,
+    pub continuity: Continuity,
+    /// Reason if the package is truncated or malformed.
+    pub error: Option<String>,
+}
+
+/// How the archive continues the masterchain range of the archives before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Continuity {
+    First,
+    Continuous,
+    /// Seqnos which are in no archive.
+    Gap(RangeInclusive<u32>),
+    /// Seqnos which are also in a previous archive.
+    Overlap(RangeInclusive<u32>),
+    /// The archive has no masterchain entries.
+    Unknown,
+}
+
+/// Scans the `archives` column family on a new thread, stopping when `task` is cancelled.
+pub(super) fn spawn_coverage<F>(db: Arc<rocksdb::DB>, task: Task, mut on_event: F)
+where
+    F: FnMut(CoverageEvent) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let event = match scan(&db, &task, &mut on_event) {
+            Ok(event) => event,
+            Err(e) => CoverageEvent::Failed(e),
+        };
+        on_event(event);
+    });
+}
+
+fn scan<F>(db: &rocksdb::DB, task: &Task, on_event: &mut F) -> Result<CoverageEvent>
+where
+    F: FnMut(CoverageEvent),
+{
+    let handle = db
+        .cf_handle("archives")
+        .context("The DB has no archives column family")?;
+
+    // Highest masterchain seqno of all archives so far
+    let mut covered_until = None::<u32>;
+    let scanned = scan_cf(db, &handle, task, |key, value, position| {
+        let package = ArchivePackage::parse(value);
+        let mc_seqnos = package
+            .entries
+            .iter()
+            .filter_map(|entry| entry.id.as_ref())
+            .filter(|(_, block_id)| block_id.workchain == -1)
+            .map(|(_, block_id)| block_id.seqno)
+            .fold(None, |range: Option<RangeInclusive<u32>>, seqno| {
+                Some(match range {
+                    Some(range) => *range.start().min(&seqno)..=*range.end().max(&seqno),
+                    None => seqno..=seqno,
+                })
+            });
+
+        let continuity = match (&mc_seqnos, covered_until) {
+            (None, _) => Continuity::Unknown,
+            (Some(_), None) => Continuity::First,
+            (Some(range), Some(until)) if *range.start() > until.saturating_add(1) => {
+                Continuity::Gap(until + 1..=range.start() - 1)
+            }
+            (Some(range), Some(until)) if *range.start() <= until => {
+                Continuity::Overlap(*range.start()..=until.min(*range.end()))
+            }
+            (Some(_), Some(_)) => Continuity::Continuous,
+        };
+        if let Some(range) = &mc_seqnos {
+            covered_until =
+                Some(covered_until.map_or(*range.end(), |until| until.max(*range.end())));
+        }
+
+        let archive = ArchiveCoverage {
+            archive_id: key
+                .get(..4)
+                .map_or(0, |id| u32::from_be_bytes(id.try_into().unwrap())),
+            key: key.to_vec(),
+            mc_seqnos,
+            size: value.len(),
+            entries: package.entries.len(),
+            continuity,
+            error: package
+                .error
+                .map(|(offset, reason)| format!("{reason} at byte {offset}")),
+        };
+        on_event(CoverageEvent::Archive {
+            archive: Box::new(archive),
+            position: position.fraction,
+        });
+        Ok(ControlFlow::Continue(()))
+    })?;
+
+    Ok(CoverageEvent::Finished { scanned })
+}
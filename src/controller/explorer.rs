@@ -0,0 +1,274 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+
+use super::archive::ArchivePackage;
+use super::models::{BlockIdFull, BlockIdShort, BlockMeta, PackageEntryId, PackageType};
+use super::repr::ReprKind;
+use super::task::Task;
+
+pub enum ExploreEvent {
+    /// Rows of all known column families except `archives`.
+    Block {
+        id: BlockIdFull,
+        items: Vec<BlockItem>,
+    },
+    /// Row of `archives`, if the DB has them and the masterchain seqno of the block is known.
+    Archive(Option<BlockItem>),
+    Failed(anyhow::Error),
+}
+
+/// Row of a column family which belongs to the explored block.
+pub struct BlockItem {
+    pub cf_name: &'static str,
+    /// What the row is for the block, e.g. `prev1` or `proof`.
+    pub name: String,
+    pub key: Vec<u8>,
+    /// Whether the row exists, missing rows are listed to show gaps.
+    pub found: bool,
+    pub summary: String,
+    pub size: Option<usize>,
+}
+
+/// Finds the full id of a block given either in full or as `workchain:shard:seqno`.
+pub(super) fn resolve_block_id(db: &rocksdb::DB, text: &str) -> Result<BlockIdFull> {
+    let text = text.trim();
+    if let Ok(id) = text.parse::<BlockIdFull>() {
+        return Ok(id);
+    }
+    let short = text
+        .parse::<BlockIdShort>()
+        .context("Expected a block id as workchain:shard:seqno[:root_hash:file_hash]")?;
+
+    // Shard states keep both hashes of the block
+    if let Some(handle) = db.cf_handle("shard_states") {
+        let value = db
+            .get_pinned_cf(&handle, short.to_bytes())
+            .context("Failed to read shard state")?;
+        if let Some(value) = value.filter(|value| value.len() == 32 * 3) {
+            let root_hash = value[32..64].try_into().unwrap();
+            let file_hash = value[64..96].try_into().unwrap();
+            return Ok(full_id(short, root_hash, file_hash));
+        }
+    }
+
+    // Otherwise the file hash is the hash of the stored block
+    if let Some(handle) = db.cf_handle("package_entries") {
+        let prefix = short.to_bytes();
+        let mode = rocksdb::IteratorMode::From(&prefix, rocksdb::Direction::Forward);
+        for item in db.iterator_cf(&handle, mode) {
+            let (key, value) = item.context("Failed to read package entry")?;
+            let Some(id) = PackageEntryId::read(&key).filter(|id| id.block_id == short) else {
+                break;
+            };
+            if id.package_type == PackageType::Block {
+                let file_hash = Sha256::digest(&value).into();
+                return Ok(full_id(short, id.root_hash, file_hash));
+            }
+        }
+    }
+
+    anyhow::bail!("Block {short} not found in shard_states or package_entries")
+}
+
+/// Collects the rows of all known column families which belong to the block.
+fn explore_block(db: &rocksdb::DB, id: &BlockIdFull) -> Result<Vec<BlockItem>> {
+    let mut explorer = Explorer {
+        db,
+        items: Vec::new(),
+    };
+
+    explorer.add(
+        "block_handles",
+        "handle",
+        id.root_hash.to_vec(),
+        ReprKind::BlockMeta,
+    )?;
+
+    for cf_name in ["prev1", "prev2", "next1", "next2"] {
+        explorer.add(
+            cf_name,
+            cf_name,
+            id.root_hash.to_vec(),
+            ReprKind::BlockIdFull,
+        )?;
+    }
+
+    for package_type in [
+        PackageType::Block,
+        PackageType::Proof,
+        PackageType::ProofLink,
+    ] {
+        let key = PackageEntryId {
+            block_id: id.short(),
+            root_hash: id.root_hash,
+            package_type,
+        };
+        explorer.add(
+            "package_entries",
+            package_type.name(),
+            key.to_bytes().to_vec(),
+            ReprKind::Boc,
+        )?;
+    }
+
+    explorer.add(
+        "shard_states",
+        "state",
+        id.short().to_bytes().to_vec(),
+        ReprKind::ShardState,
+    )?;
+
+    Ok(explorer.items)
+}
+
+struct Explorer<'a> {
+    db: &'a rocksdb::DB,
+    items: Vec<BlockItem>,
+}
+
+impl Explorer<'_> {
+    /// Lists the row if the DB has the column family.
+    fn add(
+        &mut self,
+        cf_name: &'static str,
+        name: &str,
+        key: Vec<u8>,
+        repr: ReprKind,
+    ) -> Result<()> {
+        let Some(handle) = self.db.cf_handle(cf_name) else {
+            return Ok(());
+        };
+        let value = self
+            .db
+            .get_cf(&handle, &key)
+            .with_context(|| format!("Failed to read {cf_name}"))?;
+
+        let (summary, size) = match &value {
+            Some(value) => (repr.decode(&key, value).to_string(), Some(value.len())),
+            None => ("missing".to_owned(), None),
+        };
+        self.items.push(BlockItem {
+            cf_name,
+            name: name.to_owned(),
+            key,
+            found: value.is_some(),
+            summary,
+            size,
+        });
+        Ok(())
+    }
+}
+
+/// Resolves the block id and lists the rows of the block on a new thread,
+/// stopping when `task` is cancelled.
+///
+/// Archives are large, so the other rows are sent before the archive is parsed.
+pub(super) fn spawn_explore<F>(db: Arc<rocksdb::DB>, text: String, task: Task, mut on_event: F)
+where
+    F: FnMut(ExploreEvent) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let event = match explore(&db, &text, &task, &mut on_event) {
+            Ok(archive) => ExploreEvent::Archive(archive),
+            Err(e) => ExploreEvent::Failed(e),
+        };
+        on_event(event);
+    });
+}
+
+fn explore<F>(
+    db: &rocksdb::DB,
+    text: &str,
+    task: &Task,
+    on_event: &mut F,
+) -> Result<Option<BlockItem>>
+where
+    F: FnMut(ExploreEvent),
+{
+    let id = resolve_block_id(db, text)?;
+    let items = explore_block(db, &id)?;
+    on_event(ExploreEvent::Block { id, items });
+    if task.is_cancelled() {
+        return Ok(None);
+    }
+    find_archive(db, &id, task)
+}
+
+/// Lists the archive which should contain the block, i.e. the last one starting
+/// at or before its masterchain seqno.
+fn find_archive(db: &rocksdb::DB, id: &BlockIdFull, task: &Task) -> Result<Option<BlockItem>> {
+    let Some(handle) = db.cf_handle("archives") else {
+        return Ok(None);
+    };
+
+    // Archives are keyed by the seqno of their first masterchain block
+    let mc_seqno = match id.workchain {
+        -1 => id.seqno,
+        _ => {
+            let meta = match db.cf_handle("block_handles") {
+                Some(handle) => db
+                    .get_pinned_cf(&handle, id.root_hash)
+                    .context("Failed to read block_handles")?,
+                None => None,
+            };
+            match meta.as_deref().and_then(BlockMeta::read) {
+                Some(meta) => meta.mc_ref_seqno,
+                None => return Ok(None),
+            }
+        }
+    };
+
+    let key = mc_seqno.to_be_bytes();
+    let mut iter = db.raw_iterator_cf(&handle);
+    iter.seek_for_prev(key);
+    let Some((archive_key, package)) = iter.item() else {
+        iter.status().context("Failed to read archive")?;
+        return Ok(Some(BlockItem {
+            cf_name: "archives",
+            name: "archive".to_owned(),
+            key: key.to_vec(),
+            found: false,
+            summary: format!("no archive before mc seqno {mc_seqno}"),
+            size: None,
+        }));
+    };
+    if task.is_cancelled() {
+        return Ok(None);
+    }
+
+    let archive_id = ReprKind::U32Be.decode(archive_key, archive_key);
+    let entries = ArchivePackage::parse(package)
+        .entries
+        .into_iter()
+        .filter_map(|entry| match entry.id {
+            Some((package_type, block_id)) if block_id == *id => Some(package_type.name()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let summary = if entries.is_empty() {
+        format!("not in archive {archive_id}")
+    } else {
+        format!("archive {archive_id}: {}", entries.join(", "))
+    };
+    Ok(Some(BlockItem {
+        cf_name: "archives",
+        name: "archive".to_owned(),
+        key: archive_key.to_vec(),
+        found: !entries.is_empty(),
+        summary,
+        size: Some(package.len()),
+    }))
+}
+
+fn full_id(short: BlockIdShort, root_hash: [u8; 32], file_hash: [u8; 32]) -> BlockIdFull {
+    BlockIdFull {
+        workchain: short.workchain,
+        shard: short.shard,
+        seqno: short.seqno,
+        root_hash,
+        file_hash,
+    }
+}
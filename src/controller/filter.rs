@@ -0,0 +1,489 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Result;
+
+use super::{CfEntry, Value, ValueKind};
+
+/// Row filter over the decoded key and value, e.g.
+/// `key.workchain == -1 && key.seqno > 1000000` or `len(value) > 65536`.
+///
+/// Fields are addressed by their names in the decoded tree, starting from `key` or `value`.
+/// Supported operators are `==`, `!=`, `<`, `<=`, `>`, `>=`, `&&`, `||` and `!`.
+/// `len(field)` is the size of the field in bytes.
+#[derive(Debug)]
+pub struct Filter {
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn matches(&self, entry: &CfEntry) -> bool {
+        self.expr.eval(entry).is_true()
+    }
+}
+
+impl FromStr for Filter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+            end: s.len(),
+            depth: 0,
+        };
+        let expr = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            Some((offset, token)) => anyhow::bail!("Unexpected {token} at column {}", offset + 1),
+            None => Ok(Self { expr }),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Expr {
+    Literal(Scalar),
+    Field(Part, Vec<String>),
+    Len(Part, Vec<String>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, CmpOp, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, entry: &CfEntry) -> Scalar {
+        match self {
+            Self::Literal(value) => value.clone(),
+            Self::Field(part, path) => match find_field(entry, *part, path) {
+                Some(value) => Scalar::from_value(&value),
+                None => Scalar::Null,
+            },
+            Self::Len(part, path) => match find_field(entry, *part, path) {
+                Some(value) => Scalar::Int(value.range.len() as i128),
+                None => Scalar::Null,
+            },
+            Self::Not(expr) => Scalar::from_bool(!expr.eval(entry).is_true()),
+            Self::And(left, right) => {
+                Scalar::from_bool(left.eval(entry).is_true() && right.eval(entry).is_true())
+            }
+            Self::Or(left, right) => {
+                Scalar::from_bool(left.eval(entry).is_true() || right.eval(entry).is_true())
+            }
+            Self::Compare(left, op, right) => {
+                let ordering = left.eval(entry).compare(&right.eval(entry));
+                Scalar::from_bool(ordering.is_some_and(|ordering| op.test(ordering)))
+            }
+        }
+    }
+}
+
+fn find_field(entry: &CfEntry, part: Part, path: &[String]) -> Option<Value> {
+    let mut value = match part {
+        Part::Key => entry.decoded_key.clone(),
+        Part::Value => entry.decoded_value.clone(),
+    };
+    for name in path {
+        value = value.child(name)?;
+    }
+    Some(value)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Part {
+    Key,
+    Value,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CmpOp {
+    fn test(self, ordering: Ordering) -> bool {
+        match self {
+            Self::Eq => ordering.is_eq(),
+            Self::Ne => ordering.is_ne(),
+            Self::Lt => ordering.is_lt(),
+            Self::Le => ordering.is_le(),
+            Self::Gt => ordering.is_gt(),
+            Self::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Scalar {
+    Null,
+    Int(i128),
+    Str(String),
+}
+
+impl Scalar {
+    fn from_bool(value: bool) -> Self {
+        Self::Int(value as i128)
+    }
+
+    fn from_value(value: &Value) -> Self {
+        if let Some(number) = value.as_number() {
+            return Self::Int(number);
+        }
+        match &value.kind {
+            ValueKind::Error { .. } => Self::Null,
+            _ => Self::Str(value.to_string()),
+        }
+    }
+
+    fn is_true(&self) -> bool {
+        match self {
+            Self::Null => false,
+            Self::Int(value) => *value != 0,
+            Self::Str(value) => !value.is_empty(),
+        }
+    }
+
+    /// Numbers are compared numerically, everything else by the displayed text.
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Null, _) | (_, Self::Null) => None,
+            (Self::Int(left), Self::Int(right)) => Some(left.cmp(right)),
+            (Self::Str(left), Self::Str(right)) => Some(left.cmp(right)),
+            (Self::Int(left), Self::Str(right)) => Some(left.to_string().cmp(right)),
+            (Self::Str(left), Self::Int(right)) => Some(left.cmp(&right.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Int(i128),
+    Str(String),
+    Op(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ident(name) => write!(f, "`{name}`"),
+            Self::Int(value) => write!(f, "`{value}`"),
+            Self::Str(value) => write!(f, "{value:?}"),
+            Self::Op(op) => write!(f, "`{op}`"),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<(usize, Token)>> {
+    const OPERATORS: [&str; 13] = [
+        "==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "(", ")", ".", "-",
+    ];
+
+    let mut tokens = Vec::new();
+    let mut rest = s;
+    loop {
+        rest = rest.trim_start();
+        let offset = s.len() - rest.len();
+        let Some(c) = rest.chars().next() else {
+            break;
+        };
+
+        let len = if c.is_ascii_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push((offset, Token::Ident(rest[..len].to_owned())));
+            len
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .unwrap_or(rest.len());
+            let text = &rest[..len];
+            let value = match text.strip_prefix("0x") {
+                Some(hex) => i128::from_str_radix(hex, 16),
+                None => text.parse(),
+            };
+            let Ok(value) = value else {
+                anyhow::bail!("Invalid number {text:?} at column {}", offset + 1);
+            };
+            tokens.push((offset, Token::Int(value)));
+            len
+        } else if c == '"' {
+            let mut value = String::new();
+            let mut chars = rest.char_indices().skip(1);
+            let len = loop {
+                match chars.next() {
+                    Some((i, '"')) => break i + 1,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, c)) => value.push(c),
+                        None => break 0,
+                    },
+                    Some((_, c)) => value.push(c),
+                    None => break 0,
+                }
+            };
+            anyhow::ensure!(len > 0, "Unterminated string at column {}", offset + 1);
+            tokens.push((offset, Token::Str(value)));
+            len
+        } else {
+            let Some(op) = OPERATORS.into_iter().find(|op| rest.starts_with(op)) else {
+                anyhow::bail!("Unexpected {c:?} at column {}", offset + 1);
+            };
+            tokens.push((offset, Token::Op(op)));
+            op.len()
+        };
+        rest = &rest[len..];
+    }
+    Ok(tokens)
+}
+
+/// Limits the recursion of `!` and parentheses, which would otherwise overflow the stack.
+const MAX_DEPTH: usize = 200;
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    end: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_and()?;
+        while self.eat_op("||") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_unary()?;
+        while self.eat_op("&&") {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        let offset = self.offset();
+        if self.eat_op("!") {
+            let expr = self.nested(offset, Self::parse_unary)?;
+            return Ok(Expr::Not(Box::new(expr)));
+        }
+        self.parse_compare()
+    }
+
+    fn parse_compare(&mut self) -> Result<Expr> {
+        const OPERATORS: [(&str, CmpOp); 6] = [
+            ("==", CmpOp::Eq),
+            ("!=", CmpOp::Ne),
+            ("<=", CmpOp::Le),
+            (">=", CmpOp::Ge),
+            ("<", CmpOp::Lt),
+            (">", CmpOp::Gt),
+        ];
+
+        let left = self.parse_operand()?;
+        for (name, op) in OPERATORS {
+            if self.eat_op(name) {
+                let right = self.parse_operand()?;
+                return Ok(Expr::Compare(Box::new(left), op, Box::new(right)));
+            }
+        }
+        Ok(left)
+    }
+
+    fn parse_operand(&mut self) -> Result<Expr> {
+        let offset = self.offset();
+        match self.next() {
+            Some(Token::Int(value)) => Ok(Expr::Literal(Scalar::Int(value))),
+            Some(Token::Str(value)) => Ok(Expr::Literal(Scalar::Str(value))),
+            Some(Token::Op("-")) => {
+                let offset = self.offset();
+                match self.next() {
+                    Some(Token::Int(value)) => Ok(Expr::Literal(Scalar::Int(-value))),
+                    _ => anyhow::bail!("Expected a number at column {}", offset + 1),
+                }
+            }
+            Some(Token::Op("(")) => {
+                let expr = self.nested(offset, Self::parse_or)?;
+                self.expect_op(")")?;
+                Ok(expr)
+            }
+            Some(Token::Ident(name)) if name == "len" => {
+                self.expect_op("(")?;
+                let (part, path) = self.parse_path()?;
+                self.expect_op(")")?;
+                Ok(Expr::Len(part, path))
+            }
+            Some(Token::Ident(_)) => {
+                self.pos -= 1;
+                let (part, path) = self.parse_path()?;
+                Ok(Expr::Field(part, path))
+            }
+            Some(token) => anyhow::bail!("Unexpected {token} at column {}", offset + 1),
+            None => anyhow::bail!("Unexpected end of expression"),
+        }
+    }
+
+    fn parse_path(&mut self) -> Result<(Part, Vec<String>)> {
+        let offset = self.offset();
+        let part = match self.next() {
+            Some(Token::Ident(name)) if name == "key" => Part::Key,
+            Some(Token::Ident(name)) if name == "value" => Part::Value,
+            _ => anyhow::bail!("Expected `key` or `value` at column {}", offset + 1),
+        };
+
+        let mut path = Vec::new();
+        while self.eat_op(".") {
+            let offset = self.offset();
+            match self.next() {
+                Some(Token::Ident(name)) => path.push(name),
+                _ => anyhow::bail!("Expected a field name at column {}", offset + 1),
+            }
+        }
+        Ok((part, path))
+    }
+
+    /// Parses the operand of the `!` or `(` at `offset` one level deeper.
+    fn nested<T>(
+        &mut self,
+        offset: usize,
+        parse: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        anyhow::ensure!(
+            self.depth < MAX_DEPTH,
+            "Expression is nested too deeply at column {}",
+            offset + 1
+        );
+        self.depth += 1;
+        let res = parse(self);
+        self.depth -= 1;
+        res
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let (_, token) = self.tokens.get(self.pos)?;
+        self.pos += 1;
+        Some(token.clone())
+    }
+
+    fn offset(&self) -> usize {
+        match self.tokens.get(self.pos) {
+            Some((offset, _)) => *offset,
+            None => self.end,
+        }
+    }
+
+    fn eat_op(&mut self, op: &str) -> bool {
+        match self.tokens.get(self.pos) {
+            Some((_, Token::Op(token))) if *token == op => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect_op(&mut self, op: &str) -> Result<()> {
+        let offset = self.offset();
+        anyhow::ensure!(self.eat_op(op), "Expected `{op}` at column {}", offset + 1);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::{BlockIdShort, ReprKind};
+
+    fn entry() -> CfEntry {
+        let key = BlockIdShort {
+            workchain: -1,
+            shard: 0x8000000000000000,
+            seqno: 1000,
+        }
+        .to_bytes();
+        let value = [1, 2, 3, 4, 5];
+        CfEntry {
+            key: key.into(),
+            value: value.into(),
+            decoded_key: ReprKind::BlockIdShort.decode(&key, &key),
+            decoded_value: ReprKind::Hex.decode(&key, &value),
+        }
+    }
+
+    fn matches(expr: &str) -> bool {
+        expr.parse::<Filter>().unwrap().matches(&entry())
+    }
+
+    fn error(expr: &str) -> String {
+        expr.parse::<Filter>().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn fields() {
+        assert!(matches("key.workchain == -1 && key.seqno > 999"));
+        assert!(matches("key.shard == 0x8000000000000000"));
+        assert!(matches("value == \"0102030405\""));
+        assert!(matches("len(value) == 5 && len(key.seqno) == 4"));
+        assert!(!matches("key.seqno >= 1001"));
+    }
+
+    #[test]
+    fn missing_fields_never_match() {
+        assert!(!matches("key.unknown == 0"));
+        assert!(!matches("key.unknown != 0"));
+        assert!(matches("!key.unknown"));
+    }
+
+    #[test]
+    fn precedence() {
+        // `&&` binds tighter than `||`
+        assert!(matches(
+            "key.seqno == 1000 || key.seqno == 1 && key.seqno == 2"
+        ));
+        assert!(!matches(
+            "(key.seqno == 1000 || key.seqno == 1) && key.seqno == 2"
+        ));
+        // `!` applies to the comparison after it
+        assert!(matches("!key.seqno == 1 && key.seqno == 1000"));
+        assert!(!matches("!(key.seqno == 1 || key.seqno == 1000)"));
+        assert!(matches("!!key.seqno"));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(error("key =="), "Unexpected end of expression");
+        assert_eq!(error("key == 1 )"), "Unexpected `)` at column 10");
+        assert_eq!(error("key = 1"), "Unexpected '=' at column 5");
+        assert_eq!(error("foo == 1"), "Expected `key` or `value` at column 1");
+        assert_eq!(error("key. == 1"), "Expected a field name at column 6");
+        assert_eq!(error("len(key"), "Expected `)` at column 8");
+        assert_eq!(error("key == - x"), "Expected a number at column 10");
+        assert_eq!(error("key == -"), "Expected a number at column 9");
+        assert_eq!(error("key == \"abc"), "Unterminated string at column 8");
+        assert_eq!(error("key == 0xzz"), "Invalid number \"0xzz\" at column 8");
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let nested = |depth: usize| format!("{}key.seqno{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(matches(&nested(MAX_DEPTH)));
+        assert_eq!(
+            error(&nested(MAX_DEPTH + 1)),
+            format!(
+                "Expression is nested too deeply at column {}",
+                MAX_DEPTH + 1
+            )
+        );
+        assert!(error(&"(".repeat(100_000)).starts_with("Expression is nested too deeply"));
+        assert!(error(&"!".repeat(100_000)).starts_with("Expression is nested too deeply"));
+    }
+}
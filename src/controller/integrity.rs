@@ -0,0 +1,324 @@
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+
+use super::archive::ArchivePackage;
+use super::boc::Boc;
+use super::chain::{self, INVERSE_LINK_CFS};
+use super::models::{BlockIdFull, BlockIdShort, PackageEntryId, PackageType};
+use super::task::{scan_cf, Task};
+
+/// Column families with BOCs whose hashes are stored next to them.
+const VERIFIED_CFS: [&str; 2] = ["package_entries", "archives"];
+
+/// Scan run in the background by [`spawn_verify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrityCheck {
+    /// Stored hashes of the BOCs in [`VERIFIED_CFS`].
+    Hashes,
+    /// Prev/next links agree with each other and point to stored blocks.
+    ChainLinks,
+}
+
+impl IntegrityCheck {
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Hashes => "Hash verification",
+            Self::ChainLinks => "Chain link check",
+        }
+    }
+
+    /// What the check can't verify.
+    pub fn limitations(self) -> Option<&'static str> {
+        match self {
+            Self::Hashes => Some(
+                "File hashes of package_entries blocks are only checked where shard_states, \
+                 key_blocks or a neighbour link stores the full block id.",
+            ),
+            Self::ChainLinks => None,
+        }
+    }
+}
+
+pub enum IntegrityEvent {
+    Issue(Box<IntegrityIssue>),
+    /// Position is the estimated fraction of all entries already verified.
+    Progress {
+        scanned: usize,
+        position: f64,
+    },
+    Finished {
+        scanned: usize,
+    },
+    Failed(anyhow::Error),
+}
+
+pub struct IntegrityIssue {
+    pub cf_name: &'static str,
+    pub key: Vec<u8>,
+    /// Package entry id, archive entry name or linked block id.
+    pub entry: String,
+    pub problem: String,
+}
+
+/// Runs the check on a new thread, stopping when `task` is cancelled.
+pub(super) fn spawn_verify<F>(
+    db: Arc<rocksdb::DB>,
+    check: IntegrityCheck,
+    task: Task,
+    mut on_event: F,
+) where
+    F: FnMut(IntegrityEvent) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let res = match check {
+            IntegrityCheck::Hashes => verify(&db, &task, &mut on_event),
+            IntegrityCheck::ChainLinks => chain::check_links(&db, &task, &mut on_event),
+        };
+        let event = match res {
+            Ok(event) => event,
+            Err(e) => IntegrityEvent::Failed(e),
+        };
+        on_event(event);
+    });
+}
+
+fn verify<F>(db: &rocksdb::DB, task: &Task, on_event: &mut F) -> Result<IntegrityEvent>
+where
+    F: FnMut(IntegrityEvent),
+{
+    let cf_names = VERIFIED_CFS
+        .into_iter()
+        .filter(|cf_name| db.cf_handle(cf_name).is_some())
+        .collect::<Vec<_>>();
+
+    let file_hashes = FileHashes::new(db);
+
+    let mut scanned = 0;
+    for (i, cf_name) in cf_names.iter().copied().enumerate() {
+        if task.is_cancelled() {
+            break;
+        }
+
+        let handle = db.cf_handle(cf_name).context("Column family not found")?;
+        let cf_scanned = scan_cf(db, &handle, task, |key, value, position| {
+            let issues = match cf_name {
+                "package_entries" => check_package_entry(&file_hashes, key, value)?,
+                _ => check_archive(value),
+            };
+            for (entry, problem) in issues {
+                on_event(IntegrityEvent::Issue(Box::new(IntegrityIssue {
+                    cf_name,
+                    key: key.to_vec(),
+                    entry,
+                    problem,
+                })));
+            }
+
+            if position.report_due {
+                on_event(IntegrityEvent::Progress {
+                    scanned: scanned + position.scanned,
+                    position: (i as f64 + position.fraction) / cf_names.len() as f64,
+                });
+            }
+            Ok(ControlFlow::Continue(()))
+        })?;
+        scanned += cf_scanned;
+    }
+
+    Ok(IntegrityEvent::Finished { scanned })
+}
+
+/// Returns the entry and the problem for every mismatch.
+fn check_package_entry(
+    file_hashes: &FileHashes<'_>,
+    key: &[u8],
+    value: &[u8],
+) -> Result<Vec<(String, String)>> {
+    let Some(id) = PackageEntryId::read(key) else {
+        return Ok(vec![(
+            hex::encode(key),
+            "invalid package entry id".to_owned(),
+        )]);
+    };
+
+    // Proofs have no file hash of their own
+    let file_hash = match id.package_type {
+        PackageType::Block => file_hashes.find(&id.block_id, &id.root_hash)?,
+        _ => None,
+    };
+
+    let entry = id.to_string();
+    Ok(
+        check_boc(value, id.package_type, &id.root_hash, file_hash.as_ref())
+            .into_iter()
+            .map(|problem| (entry.clone(), problem))
+            .collect(),
+    )
+}
+
+/// Looks up the file hash of a block in the rows which store its full id.
+struct FileHashes<'a> {
+    db: &'a rocksdb::DB,
+    shard_states: Option<Arc<rocksdb::BoundColumnFamily<'a>>>,
+    key_blocks: Option<Arc<rocksdb::BoundColumnFamily<'a>>>,
+    /// Links of the block and the inverse links of the linked blocks.
+    links: Vec<LinkHandles<'a>>,
+}
+
+type LinkHandles<'a> = (
+    Arc<rocksdb::BoundColumnFamily<'a>>,
+    Vec<Arc<rocksdb::BoundColumnFamily<'a>>>,
+);
+
+impl<'a> FileHashes<'a> {
+    fn new(db: &'a rocksdb::DB) -> Self {
+        let links = INVERSE_LINK_CFS
+            .into_iter()
+            .filter_map(|(cf_name, inverse_cf_names)| {
+                let inverse = inverse_cf_names
+                    .into_iter()
+                    .filter_map(|cf_name| db.cf_handle(cf_name))
+                    .collect();
+                Some((db.cf_handle(cf_name)?, inverse))
+            })
+            .collect();
+
+        Self {
+            db,
+            shard_states: db.cf_handle("shard_states"),
+            key_blocks: db.cf_handle("key_blocks"),
+            links,
+        }
+    }
+
+    fn find(&self, block_id: &BlockIdShort, root_hash: &[u8; 32]) -> Result<Option<[u8; 32]>> {
+        let matches = |id: BlockIdFull| (id.root_hash == *root_hash).then_some(id.file_hash);
+
+        // Shard states keep both hashes of the block
+        if let Some(handle) = &self.shard_states {
+            let value = self
+                .db
+                .get_pinned_cf(handle, block_id.to_bytes())
+                .context("Failed to read shard state")?;
+            if let Some(value) = value.filter(|value| value.len() == 32 * 3) {
+                if value[32..64] == root_hash[..] {
+                    return Ok(Some(value[64..96].try_into().unwrap()));
+                }
+            }
+        }
+
+        // Key blocks are keyed by their masterchain seqno
+        if let (Some(handle), -1) = (&self.key_blocks, block_id.workchain) {
+            let value = self
+                .db
+                .get_pinned_cf(handle, block_id.seqno.to_be_bytes())
+                .context("Failed to read key block")?;
+            if let Some(file_hash) = value
+                .as_deref()
+                .and_then(BlockIdFull::read)
+                .and_then(matches)
+            {
+                return Ok(Some(file_hash));
+            }
+        }
+
+        // Linked blocks store the full id in their inverse links
+        for (handle, inverse_handles) in &self.links {
+            let value = self
+                .db
+                .get_pinned_cf(handle, root_hash)
+                .context("Failed to read link")?;
+            let Some(linked) = value.as_deref().and_then(BlockIdFull::read) else {
+                continue;
+            };
+            for inverse_handle in inverse_handles {
+                let value = self
+                    .db
+                    .get_pinned_cf(inverse_handle, linked.root_hash)
+                    .context("Failed to read link")?;
+                if let Some(file_hash) = value
+                    .as_deref()
+                    .and_then(BlockIdFull::read)
+                    .and_then(matches)
+                {
+                    return Ok(Some(file_hash));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+fn check_archive(value: &[u8]) -> Vec<(String, String)> {
+    let package = ArchivePackage::parse(value);
+
+    let mut issues = Vec::new();
+    for entry in package.entries {
+        let Some((package_type, block_id)) = entry.id else {
+            issues.push((entry.name, "unknown entry name".to_owned()));
+            continue;
+        };
+
+        // Proofs are listed with the file hash of the block
+        let file_hash = (package_type == PackageType::Block).then_some(&block_id.file_hash);
+        let data = &value[entry.data];
+        for problem in check_boc(data, package_type, &block_id.root_hash, file_hash) {
+            issues.push((entry.name.clone(), problem));
+        }
+    }
+    if let Some((offset, reason)) = package.error {
+        issues.push((
+            "package".to_owned(),
+            format!("invalid package at byte {offset}: {reason}"),
+        ));
+    }
+    issues
+}
+
+fn check_boc(
+    data: &[u8],
+    package_type: PackageType,
+    root_hash: &[u8; 32],
+    file_hash: Option<&[u8; 32]>,
+) -> Vec<String> {
+    let boc = match Boc::parse(data) {
+        Ok(boc) => boc,
+        Err((offset, reason)) => return vec![format!("invalid BOC at byte {offset}: {reason}")],
+    };
+
+    let mut problems = Vec::new();
+    if let Some((_, false)) = boc.crc {
+        problems.push("CRC mismatch".to_owned());
+    }
+
+    let computed = match package_type {
+        PackageType::Block => Some(boc.root_hash()),
+        PackageType::Proof | PackageType::ProofLink => boc.proven_root_hash(),
+        PackageType::Unknown(_) => return problems,
+    };
+    match computed {
+        Some(computed) if computed != *root_hash => problems.push(format!(
+            "root hash mismatch: expected {}, got {}",
+            hex::encode(root_hash),
+            hex::encode(computed)
+        )),
+        Some(_) => {}
+        None => problems.push("proof has no merkle proof cell".to_owned()),
+    }
+
+    if let Some(file_hash) = file_hash {
+        let computed: [u8; 32] = Sha256::digest(data).into();
+        if computed != *file_hash {
+            problems.push(format!(
+                "file hash mismatch: expected {}, got {}",
+                hex::encode(file_hash),
+                hex::encode(computed)
+            ));
+        }
+    }
+    problems
+}
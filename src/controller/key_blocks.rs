@@ -0,0 +1,383 @@
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+use super::boc::{Boc, Cell, CellType};
+use super::models::{BlockIdFull, PackageEntryId, PackageType};
+use super::task::{scan_cf, Task};
+use super::value::format_unix_time;
+
+pub enum KeyBlockEvent {
+    /// Position is the estimated fraction of all key blocks already listed.
+    Block {
+        key_block: Box<KeyBlock>,
+        position: f64,
+    },
+    Finished {
+        scanned: usize,
+    },
+    Failed(anyhow::Error),
+}
+
+/// Row of the `key_blocks` column family with what its block data tells.
+pub struct KeyBlock {
+    pub seqno: u32,
+    pub id: Option<BlockIdFull>,
+    /// Only known if the block is in `package_entries`.
+    pub gen_utime: Option<u32>,
+    pub config: ConfigChange,
+}
+
+impl KeyBlock {
+    pub fn gen_time(&self) -> Option<String> {
+        self.gen_utime.map(|secs| format_unix_time(secs as u64))
+    }
+}
+
+/// Config of the key block compared with the previous key block with known config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigChange {
+    /// The block data is missing or can't be decoded.
+    Unknown,
+    First,
+    Unchanged,
+    /// Numbers of the added, removed or changed params.
+    Changed(Vec<u32>),
+}
+
+/// Lists `key_blocks` on a new thread, stopping when `task` is cancelled.
+pub(super) fn spawn_key_blocks<F>(db: Arc<rocksdb::DB>, task: Task, mut on_event: F)
+where
+    F: FnMut(KeyBlockEvent) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let event = match scan(&db, &task, &mut on_event) {
+            Ok(event) => event,
+            Err(e) => KeyBlockEvent::Failed(e),
+        };
+        on_event(event);
+    });
+}
+
+fn scan<F>(db: &rocksdb::DB, task: &Task, on_event: &mut F) -> Result<KeyBlockEvent>
+where
+    F: FnMut(KeyBlockEvent),
+{
+    let handle = db
+        .cf_handle("key_blocks")
+        .context("The DB has no key_blocks column family")?;
+    let package_entries = db.cf_handle("package_entries");
+
+    let mut prev_config = None::<BTreeMap<u32, [u8; 32]>>;
+    let scanned = scan_cf(db, &handle, task, |key, value, position| {
+        let id = BlockIdFull::read(value);
+        let mut block = None;
+        if let (Some(id), Some(package_entries)) = (&id, &package_entries) {
+            let entry_id = PackageEntryId {
+                block_id: id.short(),
+                root_hash: id.root_hash,
+                package_type: PackageType::Block,
+            };
+            let data = db
+                .get_pinned_cf(package_entries, entry_id.to_bytes())
+                .context("Failed to read package entry")?;
+            block = data.as_deref().and_then(BlockSummary::parse);
+        }
+
+        let config = match block.as_ref().and_then(|block| block.config.as_ref()) {
+            None => ConfigChange::Unknown,
+            Some(config) => {
+                let change = match &prev_config {
+                    None => ConfigChange::First,
+                    Some(prev) => changed_params(prev, config),
+                };
+                prev_config = Some(config.clone());
+                change
+            }
+        };
+
+        let key_block = KeyBlock {
+            seqno: key
+                .get(..4)
+                .map_or(0, |seqno| u32::from_be_bytes(seqno.try_into().unwrap())),
+            id,
+            gen_utime: block.map(|block| block.gen_utime),
+            config,
+        };
+        on_event(KeyBlockEvent::Block {
+            key_block: Box::new(key_block),
+            position: position.fraction,
+        });
+        Ok(ControlFlow::Continue(()))
+    })?;
+
+    Ok(KeyBlockEvent::Finished { scanned })
+}
+
+fn changed_params(
+    prev: &BTreeMap<u32, [u8; 32]>,
+    config: &BTreeMap<u32, [u8; 32]>,
+) -> ConfigChange {
+    let mut changed = prev
+        .iter()
+        .filter(|(param, hash)| config.get(param) != Some(hash))
+        .map(|(param, _)| *param)
+        .chain(
+            config
+                .keys()
+                .filter(|param| !prev.contains_key(param))
+                .copied(),
+        )
+        .collect::<Vec<_>>();
+    changed.sort_unstable();
+
+    if changed.is_empty() {
+        ConfigChange::Unchanged
+    } else {
+        ConfigChange::Changed(changed)
+    }
+}
+
+/// Fields of a masterchain block used by the timeline.
+struct BlockSummary {
+    gen_utime: u32,
+    /// Hashes of the config param values by their number.
+    config: Option<BTreeMap<u32, [u8; 32]>>,
+}
+
+impl BlockSummary {
+    const BLOCK_TAG: u64 = 0x11ef55aa;
+    const BLOCK_INFO_TAG: u64 = 0x9bc7a987;
+    const BLOCK_EXTRA_TAG: u64 = 0x4a33f6fd;
+    const MC_BLOCK_EXTRA_TAG: u64 = 0xcca5;
+
+    fn parse(data: &[u8]) -> Option<Self> {
+        let boc = Boc::parse(data).ok()?;
+        let root = boc.cells.get(*boc.roots.first()?)?;
+        if BitReader::new(root)?.uint(32)? != Self::BLOCK_TAG {
+            return None;
+        }
+
+        // Version, flags, seqnos and shard ident come before the time
+        let info = boc.cells.get(*root.refs.first()?)?;
+        let mut reader = BitReader::new(info)?;
+        if reader.uint(32)? != Self::BLOCK_INFO_TAG {
+            return None;
+        }
+        reader.skip(32 + 8 + 8 + 32 + 32 + 104)?;
+        let gen_utime = reader.uint(32)? as u32;
+
+        Some(Self {
+            gen_utime,
+            config: Self::config(&boc, root),
+        })
+    }
+
+    /// The config is the last field of the masterchain extra of key blocks.
+    fn config(boc: &Boc, root: &Cell) -> Option<BTreeMap<u32, [u8; 32]>> {
+        let extra = boc.cells.get(*root.refs.get(3)?)?;
+        let mut reader = BitReader::new(extra)?;
+        if reader.uint(32)? != Self::BLOCK_EXTRA_TAG {
+            return None;
+        }
+        reader.skip(256 + 256)?;
+        if !reader.bit()? {
+            return None;
+        }
+
+        let custom = boc.cells.get(*extra.refs.get(3)?)?;
+        let mut reader = BitReader::new(custom)?;
+        if reader.uint(16)? != Self::MC_BLOCK_EXTRA_TAG || !reader.bit()? {
+            return None;
+        }
+        dict_value_hashes(boc, *custom.refs.last()?, 32)
+    }
+}
+
+/// Walks a `Hashmap n ^Cell` and returns the hashes of the values by key.
+fn dict_value_hashes(boc: &Boc, root: usize, key_bits: u32) -> Option<BTreeMap<u32, [u8; 32]>> {
+    let mut values = BTreeMap::new();
+    let mut stack = vec![(root, 0u64, key_bits)];
+    while let Some((index, prefix, remaining)) = stack.pop() {
+        let cell = boc.cells.get(index)?;
+        let mut reader = BitReader::new(cell)?;
+        let (label, label_len) = reader.label(remaining)?;
+        if label_len > remaining {
+            return None;
+        }
+        let prefix = (prefix << label_len) | label;
+        let remaining = remaining - label_len;
+
+        if remaining == 0 {
+            let value = boc.cells.get(*cell.refs.first()?)?;
+            values.insert(prefix as u32, value.repr_hash());
+        } else {
+            let [left, right] = cell.refs.get(..2)? else {
+                return None;
+            };
+            stack.push((*left, prefix << 1, remaining - 1));
+            stack.push((*right, (prefix << 1) | 1, remaining - 1));
+        }
+    }
+    Some(values)
+}
+
+/// Reads the data bits of an ordinary cell.
+struct BitReader<'a> {
+    data: &'a [u8],
+    bit_len: usize,
+    offset: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// Pruned cells only have hashes, so the fields behind them are unknown.
+    fn new(cell: &'a Cell) -> Option<Self> {
+        (cell.cell_type == CellType::Ordinary).then_some(Self {
+            data: &cell.data,
+            bit_len: cell.bit_len,
+            offset: 0,
+        })
+    }
+
+    fn bit(&mut self) -> Option<bool> {
+        if self.offset >= self.bit_len {
+            return None;
+        }
+        let bit = self.data[self.offset / 8] & (0x80 >> (self.offset % 8)) != 0;
+        self.offset += 1;
+        Some(bit)
+    }
+
+    fn uint(&mut self, bits: u32) -> Option<u64> {
+        (0..bits).try_fold(0, |value, _| Some((value << 1) | u64::from(self.bit()?)))
+    }
+
+    fn skip(&mut self, bits: usize) -> Option<()> {
+        self.offset += bits;
+        (self.offset <= self.bit_len).then_some(())
+    }
+
+    /// Reads a `HmLabel` of at most `max_len` bits, returns its bits and length.
+    fn label(&mut self, max_len: u32) -> Option<(u64, u32)> {
+        // Bits needed for a number up to max_len
+        let len_bits = u32::BITS - max_len.leading_zeros();
+        if !self.bit()? {
+            // Unary length
+            let mut len = 0;
+            while self.bit()? {
+                len += 1;
+            }
+            Some((self.uint(len)?, len))
+        } else if !self.bit()? {
+            let len = self.uint(len_bits)? as u32;
+            Some((self.uint(len)?, len))
+        } else {
+            let bit = u64::from(self.bit()?);
+            let len = self.uint(len_bits)? as u32;
+            let value = if bit == 1 && len > 0 {
+                (1 << len) - 1
+            } else {
+                0
+            };
+            Some((value, len))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::boc::tests::{boc, cell};
+
+    /// The lowest `len` bits of the value, zero-extended.
+    fn bits(value: u64, len: u32) -> String {
+        (0..len)
+            .rev()
+            .map(|i| match value.checked_shr(i).unwrap_or_default() & 1 {
+                1 => '1',
+                _ => '0',
+            })
+            .collect()
+    }
+
+    /// Masterchain block with config params 1 and 34, or without a config.
+    fn block(gen_utime: u32, key_block: bool) -> Vec<u8> {
+        let info = format!(
+            "{}{}{}",
+            bits(BlockSummary::BLOCK_INFO_TAG, 32),
+            bits(0, 216),
+            bits(gen_utime as u64, 32)
+        );
+        let extra = format!(
+            "{}{}{}",
+            bits(BlockSummary::BLOCK_EXTRA_TAG, 32),
+            bits(0, 512),
+            bits(1, 1)
+        );
+        let custom = format!(
+            "{}{}",
+            bits(BlockSummary::MC_BLOCK_EXTRA_TAG, 16),
+            bits(key_block as u64, 1)
+        );
+
+        // Keys 1 and 34 share 26 zero bits, then fork into `00001` and `00010`
+        let dict_root = format!("11 0 {}", bits(26, 6));
+        let left = format!("10 {} 00001", bits(5, 3));
+        let right = "0 11111 0 00010";
+
+        boc(&[
+            cell(&bits(BlockSummary::BLOCK_TAG, 32), &[1, 2, 3, 4]),
+            cell(&info, &[]),
+            cell("", &[]),
+            cell("", &[]),
+            cell(&extra, &[5, 6, 7, 8]),
+            cell("", &[]),
+            cell("", &[]),
+            cell("", &[]),
+            cell(&custom, &[9]),
+            cell(&dict_root, &[10, 11]),
+            cell(&left, &[12]),
+            cell(right, &[13]),
+            cell("1010", &[]),
+            cell("1", &[]),
+        ])
+    }
+
+    #[test]
+    fn key_block_summary() {
+        let data = block(1700000000, true);
+        let summary = BlockSummary::parse(&data).unwrap();
+        assert_eq!(summary.gen_utime, 1700000000);
+
+        let cells = Boc::parse(&data).unwrap().cells;
+        let expected = BTreeMap::from([(1, cells[12].repr_hash()), (34, cells[13].repr_hash())]);
+        assert_eq!(summary.config, Some(expected));
+    }
+
+    #[test]
+    fn block_without_config() {
+        let summary = BlockSummary::parse(&block(1234, false)).unwrap();
+        assert_eq!(summary.gen_utime, 1234);
+        assert_eq!(summary.config, None);
+
+        assert!(BlockSummary::parse(&boc(&[cell("1010", &[])])).is_none());
+    }
+
+    #[test]
+    fn config_changes() {
+        let prev = BTreeMap::from([(1, [1; 32]), (34, [2; 32])]);
+        assert_eq!(changed_params(&prev, &prev), ConfigChange::Unchanged);
+
+        let config = BTreeMap::from([(1, [1; 32]), (5, [3; 32]), (34, [4; 32])]);
+        assert_eq!(
+            changed_params(&prev, &config),
+            ConfigChange::Changed(vec![5, 34])
+        );
+        assert_eq!(
+            changed_params(&config, &prev),
+            ConfigChange::Changed(vec![5, 34])
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use super::models::{BlockIdFull, PackageEntryId, PackageType};
+
+/// Column family opened when following a block id, with the key built from the id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkTarget {
+    BlockHandle,
+    ShardState,
+    Package(PackageType),
+    Prev1,
+    Next1,
+}
+
+impl LinkTarget {
+    pub const ALL: [Self; 7] = [
+        Self::ShardState,
+        Self::Package(PackageType::Block),
+        Self::Package(PackageType::Proof),
+        Self::Package(PackageType::ProofLink),
+        Self::BlockHandle,
+        Self::Prev1,
+        Self::Next1,
+    ];
+
+    pub fn cf_name(self) -> &'static str {
+        match self {
+            Self::BlockHandle => "block_handles",
+            Self::ShardState => "shard_states",
+            Self::Package(_) => "package_entries",
+            Self::Prev1 => "prev1",
+            Self::Next1 => "next1",
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            Self::Package(package_type) => format!("package_entries ({})", package_type.name()),
+            _ => self.cf_name().to_owned(),
+        }
+    }
+
+    pub fn key(self, id: &BlockIdFull) -> Vec<u8> {
+        match self {
+            Self::ShardState => id.short().to_bytes().to_vec(),
+            Self::Package(package_type) => PackageEntryId {
+                block_id: id.short(),
+                root_hash: id.root_hash,
+                package_type,
+            }
+            .to_bytes()
+            .to_vec(),
+            Self::BlockHandle | Self::Prev1 | Self::Next1 => id.root_hash.to_vec(),
+        }
+    }
+}
@@ -1,12 +1,49 @@
-use std::marker::PhantomData;
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
 
+pub use self::boc::{Boc, CellInfo, CellSource};
+pub use self::cell_storage::CellStorage;
+pub use self::chain::ChainGraph;
+pub use self::coverage::{ArchiveCoverage, Continuity, CoverageEvent};
+pub use self::explorer::{BlockItem, ExploreEvent};
+pub use self::filter::Filter;
+pub use self::integrity::{IntegrityCheck, IntegrityEvent, IntegrityIssue};
+pub use self::key_blocks::{ConfigChange, KeyBlock, KeyBlockEvent};
+pub use self::links::LinkTarget;
+pub use self::models::{BlockIdFull, BlockIdShort};
+pub use self::node_state::NodeState;
+pub use self::repr::ReprKind;
+use self::repr::ValueRepr;
+pub use self::search::{SearchEvent, SearchMode, SearchQuery, SearchTarget};
+pub use self::task::Task;
+pub use self::transfer::TransferEvent;
+pub use self::value::{Value, ValueKind};
+
+mod archive;
+mod boc;
+mod cell_storage;
+mod chain;
+mod coverage;
+mod explorer;
+mod filter;
+mod integrity;
+mod key_blocks;
+mod links;
+mod models;
+mod node_state;
+mod repr;
+mod search;
+mod task;
+mod transfer;
+mod value;
+
 pub struct Db {
     path: PathBuf,
     column_families: Vec<String>,
+    read_only: bool,
     db: Arc<rocksdb::DB>,
 }
 
@@ -16,17 +53,66 @@ impl Db {
         let column_families =
             rocksdb::DB::list_cf(&options, &path).context("Failed to read column families")?;
 
-        let db = rocksdb::DB::open_cf(&options, &path, &column_families)
-            .map(Arc::new)
-            .context("Failed to open DB")?;
+        // A running node holds the lock, so fall back to a read-only view
+        let (db, read_only) = match rocksdb::DB::open_cf(&options, &path, &column_families) {
+            Ok(db) => (db, false),
+            Err(e) if is_lock_error(&e) => {
+                let db =
+                    rocksdb::DB::open_cf_for_read_only(&options, &path, &column_families, false)
+                        .with_context(|| format!("Failed to open DB read-only after: {e}"))?;
+                (db, true)
+            }
+            Err(e) => return Err(e).context("Failed to open DB"),
+        };
 
         Ok(Self {
             path,
             column_families,
-            db,
+            read_only,
+            db: Arc::new(db),
         })
     }
 
+    /// Cells of the stored shard states, if the DB has them.
+    pub fn cell_storage(&self) -> Option<CellStorage> {
+        CellStorage::new(self.db.clone())
+    }
+
+    /// Finds the rows of the block on a worker thread, the archive last.
+    pub fn explore_block<F>(&self, text: &str, on_event: F) -> Task
+    where
+        F: FnMut(ExploreEvent) + Send + 'static,
+    {
+        let task = Task::default();
+        explorer::spawn_explore(self.db.clone(), text.to_owned(), task.clone(), on_event);
+        task
+    }
+
+    /// Sync state of a ton-indexer node, `None` for other DBs.
+    pub fn node_state(&self) -> Result<Option<NodeState>> {
+        node_state::read(&self.db)
+    }
+
+    /// Finds the blocks linked to the block up to `depth` prev/next steps away on a worker thread.
+    pub fn chain_graph<F>(&self, text: &str, depth: u32, on_built: F) -> Task
+    where
+        F: FnOnce(Result<ChainGraph>) + Send + 'static,
+    {
+        let task = Task::default();
+        chain::spawn_build(
+            self.db.clone(),
+            text.to_owned(),
+            depth,
+            task.clone(),
+            on_built,
+        );
+        task
+    }
+
+    pub fn is_writable(&self) -> bool {
+        !self.read_only
+    }
+
     pub fn path(&self) -> &Path {
         self.path.as_ref()
     }
@@ -41,195 +127,329 @@ impl Db {
             .cf_handle(cf_name)
             .context("Column family not found")?;
 
-        let (key_repr, value_repr): (&dyn ValueRepr, &dyn ValueRepr) = match cf_name {
-            "archives" => (&UintRepr::<u32>(PhantomData), &BlobRepr),
-            "key_blocks" => (&UintRepr::<u32>(PhantomData), &BlockIdFullRepr),
-            "shard_states" => (&BlockIdShortRepr, &ShardStateRepr),
-            "prev1" | "prev2" | "next1" | "next2" => (&HexRepr, &BlockIdFullRepr),
-            "package_entries" => (&PackageEntryIdRepr, &BlobRepr),
-            "node_states" => (&NodeStatesRepr, &NodeStatesRepr),
-            _ => (&HexRepr, &HexRepr),
+        let (key_repr, value_repr) = match cf_name {
+            "archives" => (ReprKind::U32Be, ReprKind::Blob),
+            "key_blocks" => (ReprKind::U32Be, ReprKind::BlockIdFull),
+            "shard_states" => (ReprKind::BlockIdShort, ReprKind::ShardState),
+            "prev1" | "prev2" | "next1" | "next2" => (ReprKind::Hex, ReprKind::BlockIdFull),
+            "package_entries" => (ReprKind::PackageEntryId, ReprKind::Blob),
+            "node_states" => (ReprKind::NodeStates, ReprKind::NodeStates),
+            "cells" => (ReprKind::Hex, ReprKind::StoredCell),
+            "block_handles" => (ReprKind::Hex, ReprKind::BlockMeta),
+            _ => (ReprKind::Hex, ReprKind::Hex),
+        };
+
+        // Decoding these for every row would stall the table, so only the selected one is
+        let content_repr = match cf_name {
+            "archives" => Some(ReprKind::ArchivePackage),
+            "package_entries" => Some(ReprKind::Boc),
+            _ => None,
         };
 
         Ok(CfHandle {
+            name: cf_name.to_owned(),
             handle,
             key_repr,
             value_repr,
+            content_repr,
         })
     }
 
-    pub fn iter(&self, cf_handle: CfHandle<'_>) -> CfIterator<'_> {
-        let iter = self
-            .db
-            .iterator_cf(&cf_handle.handle, rocksdb::IteratorMode::Start);
-        CfIterator {
-            iter,
-            key_repr: cf_handle.key_repr,
-            value_repr: cf_handle.value_repr,
-        }
+    pub fn get(&self, cf_handle: &CfHandle<'_>, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.db
+            .get_cf(&cf_handle.handle, key)
+            .context("Failed to read value")
     }
-}
 
-pub struct CfHandle<'a> {
-    handle: Arc<rocksdb::BoundColumnFamily<'a>>,
-    key_repr: &'static dyn ValueRepr,
-    value_repr: &'static dyn ValueRepr,
-}
+    /// Writes the raw value to a file on a worker thread, without copying it out of the block cache.
+    pub fn save_value<F>(
+        &self,
+        cf_handle: &CfHandle<'_>,
+        key: &[u8],
+        path: PathBuf,
+        on_event: F,
+    ) -> Task
+    where
+        F: FnMut(TransferEvent) + Send + 'static,
+    {
+        let transfer = transfer::Transfer {
+            cf_name: cf_handle.name.clone(),
+            key: key.to_vec(),
+            path,
+        };
+        let task = Task::default();
+        transfer::spawn_save(self.db.clone(), transfer, task.clone(), on_event);
+        task
+    }
 
-pub struct CfIterator<'a> {
-    iter: rocksdb::DBIterator<'a>,
-    key_repr: &'static dyn ValueRepr,
-    value_repr: &'static dyn ValueRepr,
-}
+    /// Replaces the value with the contents of a file on a worker thread.
+    pub fn replace_value<F>(
+        &self,
+        cf_handle: &CfHandle<'_>,
+        key: &[u8],
+        path: PathBuf,
+        on_event: F,
+    ) -> Result<Task>
+    where
+        F: FnMut(TransferEvent) + Send + 'static,
+    {
+        anyhow::ensure!(!self.read_only, "DB is opened read-only");
+        let transfer = transfer::Transfer {
+            cf_name: cf_handle.name.clone(),
+            key: key.to_vec(),
+            path,
+        };
+        let task = Task::default();
+        transfer::spawn_replace(self.db.clone(), transfer, task.clone(), on_event);
+        Ok(task)
+    }
 
-impl<'a> Iterator for CfIterator<'a> {
-    type Item = (String, String);
+    pub fn multi_get(
+        &self,
+        cf_handle: &CfHandle<'_>,
+        keys: &[Vec<u8>],
+    ) -> Result<Vec<Option<Vec<u8>>>> {
+        self.db
+            .multi_get_cf(keys.iter().map(|key| (&cf_handle.handle, key)))
+            .into_iter()
+            .map(|value| value.context("Failed to read value"))
+            .collect()
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.iter.next()? {
-            Ok((key, value)) => {
-                let key_repr = self.key_repr.repr_value(&key, &key);
-                let value_repr = self.value_repr.repr_value(&key, &value);
-                Some((key_repr, value_repr))
-            }
-            Err(_) => None,
-        }
+    pub fn iter(&self, cf_handle: CfHandle<'_>) -> CfIterator<'_> {
+        let read_options = rocksdb::ReadOptions::default();
+        self.iter_with_mode(cf_handle, read_options, rocksdb::IteratorMode::Start)
     }
-}
 
-trait ValueRepr {
-    fn repr_value(&self, ctx: &[u8], value: &[u8]) -> String;
-}
+    /// Iterates entries starting from the first key not less than `key`.
+    pub fn iter_from(&self, cf_handle: CfHandle<'_>, key: &[u8]) -> CfIterator<'_> {
+        let read_options = rocksdb::ReadOptions::default();
+        let mode = rocksdb::IteratorMode::From(key, rocksdb::Direction::Forward);
+        self.iter_with_mode(cf_handle, read_options, mode)
+    }
 
-struct UintRepr<T>(PhantomData<T>);
+    /// Iterates entries of one shard within the seqno range, for keys starting with a block id.
+    pub fn iter_shard_blocks(
+        &self,
+        cf_handle: CfHandle<'_>,
+        workchain: i32,
+        shard: u64,
+        seqno: RangeInclusive<u32>,
+    ) -> CfIterator<'_> {
+        let block_id = |seqno| BlockIdShort {
+            workchain,
+            shard,
+            seqno,
+        };
 
-impl ValueRepr for UintRepr<u32> {
-    fn repr_value(&self, _: &[u8], value: &[u8]) -> String {
-        if value.len() != 4 {
-            return format!("<invalid {}>", hex::encode(value));
+        let mut read_options = rocksdb::ReadOptions::default();
+        let upper_bound = match seqno.end().checked_add(1) {
+            Some(end) => Some(block_id(end).to_bytes().to_vec()),
+            None => next_prefix(&block_id(0).to_bytes()[..BlockIdShort::PREFIX_LEN]),
+        };
+        if let Some(upper_bound) = upper_bound {
+            read_options.set_iterate_upper_bound(upper_bound);
         }
-        u32::from_be_bytes(value.try_into().unwrap()).to_string()
+
+        let from = block_id(*seqno.start()).to_bytes();
+        let mode = rocksdb::IteratorMode::From(&from, rocksdb::Direction::Forward);
+        self.iter_with_mode(cf_handle, read_options, mode)
     }
-}
 
-struct HexRepr;
+    /// Finds the distinct workchain and shard pairs of the block ids the keys start with.
+    ///
+    /// Skips over each shard with a single seek, so it only reads one key per shard.
+    pub fn find_shards(&self, cf_handle: &CfHandle<'_>) -> Result<Vec<(i32, u64)>> {
+        let mut shards = Vec::new();
+
+        let mut iter = self.db.raw_iterator_cf(&cf_handle.handle);
+        iter.seek_to_first();
+        while let Some(key) = iter.key() {
+            let key = key.to_vec();
+            let Some(id) = BlockIdShort::read(&key) else {
+                // Skip keys which are too short to contain a block id
+                let mut next = key;
+                next.push(0);
+                iter.seek(next);
+                continue;
+            };
+
+            shards.push((id.workchain, id.shard));
+            match next_prefix(&key[..BlockIdShort::PREFIX_LEN]) {
+                Some(next) => iter.seek(next),
+                None => break,
+            }
+        }
+        iter.status().context("Failed to read keys")?;
 
-impl ValueRepr for HexRepr {
-    fn repr_value(&self, _: &[u8], value: &[u8]) -> String {
-        hex::encode(value)
+        Ok(shards)
     }
-}
-
-struct BlockIdShortRepr;
 
-impl ValueRepr for BlockIdShortRepr {
-    fn repr_value(&self, _: &[u8], value: &[u8]) -> String {
-        if value.len() != 16 {
-            return format!("<invalid {}>", hex::encode(value));
+    /// Scans the column families in parallel worker threads, reporting at most
+    /// `limit` matches per column family and the scan progress to `on_event`.
+    pub fn search<F>(
+        &self,
+        cf_handles: &[CfHandle<'_>],
+        query: SearchQuery,
+        limit: usize,
+        on_event: F,
+    ) -> Task
+    where
+        F: FnMut(&str, SearchEvent) + Clone + Send + 'static,
+    {
+        let task = Task::default();
+        let query = Arc::new(query);
+        for cf_handle in cf_handles {
+            let params = cf_handle.scan_params(query.clone(), limit);
+            let cf_name = cf_handle.name.clone();
+            let mut on_event = on_event.clone();
+            search::spawn_scan(self.db.clone(), params, task.clone(), move |event| {
+                on_event(&cf_name, event)
+            });
         }
+        task
+    }
 
-        let workchain = i32::from_be_bytes(value[0..4].try_into().unwrap());
-        let shard = u64::from_be_bytes(value[4..12].try_into().unwrap());
-        let seqno = u32::from_be_bytes(value[12..16].try_into().unwrap());
-        format!("{workchain:2}:{shard:016x}:{seqno}")
+    /// Runs the integrity check on a worker thread.
+    pub fn verify<F>(&self, check: IntegrityCheck, on_event: F) -> Task
+    where
+        F: FnMut(IntegrityEvent) + Send + 'static,
+    {
+        let task = Task::default();
+        integrity::spawn_verify(self.db.clone(), check, task.clone(), on_event);
+        task
     }
-}
 
-struct BlockIdFullRepr;
+    /// Decodes every archive on a worker thread to find the masterchain range it covers.
+    pub fn archive_coverage<F>(&self, on_event: F) -> Task
+    where
+        F: FnMut(CoverageEvent) + Send + 'static,
+    {
+        let task = Task::default();
+        coverage::spawn_coverage(self.db.clone(), task.clone(), on_event);
+        task
+    }
 
-impl ValueRepr for BlockIdFullRepr {
-    fn repr_value(&self, _: &[u8], value: &[u8]) -> String {
-        if value.len() != 80 {
-            return format!("<invalid {}>", hex::encode(value));
-        }
+    /// Lists the key blocks on a worker thread, with config changes where the block is stored.
+    pub fn key_blocks<F>(&self, on_event: F) -> Task
+    where
+        F: FnMut(KeyBlockEvent) + Send + 'static,
+    {
+        let task = Task::default();
+        key_blocks::spawn_key_blocks(self.db.clone(), task.clone(), on_event);
+        task
+    }
 
-        let workchain = i32::from_be_bytes(value[0..4].try_into().unwrap());
-        let shard = u64::from_be_bytes(value[4..12].try_into().unwrap());
-        let seqno = u32::from_be_bytes(value[12..16].try_into().unwrap());
-        let root_hash = hex::encode(&value[16..48]);
-        let file_hash = hex::encode(&value[48..80]);
-        format!("{workchain:2}:{shard:016x}:{seqno}:{root_hash}:{file_hash}")
+    fn iter_with_mode(
+        &self,
+        cf_handle: CfHandle<'_>,
+        read_options: rocksdb::ReadOptions,
+        mode: rocksdb::IteratorMode<'_>,
+    ) -> CfIterator<'_> {
+        let iter = self
+            .db
+            .iterator_cf_opt(&cf_handle.handle, read_options, mode);
+        CfIterator {
+            iter,
+            key_repr: cf_handle.key_repr.repr(),
+            value_repr: cf_handle.value_repr.repr(),
+        }
     }
 }
 
-struct ShardStateRepr;
+/// Whether the DB can still be opened read-only, i.e. another process holds
+/// its lock or its files can't be written.
+fn is_lock_error(e: &rocksdb::Error) -> bool {
+    let message = e.to_string();
+    e.kind() == rocksdb::ErrorKind::IOError
+        && ["lock", "Permission denied", "Read-only file system"]
+            .iter()
+            .any(|reason| message.contains(reason))
+}
 
-impl ValueRepr for ShardStateRepr {
-    fn repr_value(&self, _: &[u8], value: &[u8]) -> String {
-        if value.len() != 32 * 3 {
-            return format!("<invalid {}>", hex::encode(value));
+/// Smallest key greater than all keys starting with `prefix`.
+fn next_prefix(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut next = prefix.to_vec();
+    while let Some(last) = next.pop() {
+        if last != u8::MAX {
+            next.push(last + 1);
+            return Some(next);
         }
-
-        let state_root = hex::encode(&value[0..32]);
-        let root_hash = hex::encode(&value[32..64]);
-        let file_hash = hex::encode(&value[64..96]);
-        format!("state_root: {state_root}, root_hash: {root_hash}, file_hash: {file_hash}")
     }
+    None
 }
 
-struct BlobRepr;
+pub struct CfHandle<'a> {
+    name: String,
+    handle: Arc<rocksdb::BoundColumnFamily<'a>>,
+    key_repr: ReprKind,
+    value_repr: ReprKind,
+    /// Decoder of values shown by size only in the table.
+    content_repr: Option<ReprKind>,
+}
 
-impl ValueRepr for BlobRepr {
-    fn repr_value(&self, _: &[u8], value: &[u8]) -> String {
-        format!("<{} bytes>", value.len())
+impl CfHandle<'_> {
+    pub fn key_repr(&self) -> ReprKind {
+        self.key_repr
     }
-}
 
-struct PackageEntryIdRepr;
+    pub fn value_repr(&self) -> ReprKind {
+        self.value_repr
+    }
 
-impl ValueRepr for PackageEntryIdRepr {
-    fn repr_value(&self, _: &[u8], value: &[u8]) -> String {
-        if value.len() != 16 + 32 + 1 {
-            return format!("<invalid {}>", hex::encode(value));
+    /// Decoder for the value of the selected row.
+    pub fn detail_repr(&self) -> ReprKind {
+        match (self.value_repr, self.content_repr) {
+            (ReprKind::Blob, Some(content_repr)) => content_repr,
+            (value_repr, _) => value_repr,
         }
+    }
 
-        let workchain = i32::from_be_bytes(value[0..4].try_into().unwrap());
-        let shard = u64::from_be_bytes(value[4..12].try_into().unwrap());
-        let seqno = u32::from_be_bytes(value[12..16].try_into().unwrap());
-        let root_hash = hex::encode(&value[16..48]);
-        let package_type = match value[48] {
-            0 => "block",
-            1 => "proof",
-            2 => "proof_link",
-            _ => "unknown",
-        };
-        format!("{workchain:2}:{shard:016x}:{seqno}:{root_hash}: {package_type}")
+    pub fn set_reprs(&mut self, key_repr: ReprKind, value_repr: ReprKind) {
+        self.key_repr = key_repr;
+        self.value_repr = value_repr;
+    }
+
+    fn scan_params(&self, query: Arc<SearchQuery>, limit: usize) -> search::ScanParams {
+        search::ScanParams {
+            cf_name: self.name.clone(),
+            key_repr: self.key_repr,
+            value_repr: self.value_repr,
+            query,
+            limit,
+        }
     }
 }
 
-struct NodeStatesRepr;
-
-impl ValueRepr for NodeStatesRepr {
-    fn repr_value(&self, ctx: &[u8], value: &[u8]) -> String {
-        const HISTORICAL_SYNC_LOW: &[u8] = b"background_sync_low";
-        const HISTORICAL_SYNC_HIGH: &[u8] = b"background_sync_high";
-
-        const LAST_UPLOADED_ARCHIVE: &[u8] = b"last_uploaded_archive";
-
-        const LAST_MC_BLOCK_ID: &[u8] = b"LastMcBlockId";
-        const INIT_MC_BLOCK_ID: &[u8] = b"InitMcBlockId";
-        const SHARDS_CLIENT_MC_BLOCK_ID: &[u8] = b"ShardsClientMcBlockId";
-
-        const DB_VERSION_KEY: &[u8] = b"db_version";
-
-        if ctx == value {
-            String::from_utf8_lossy(value).to_string()
-        } else {
-            match ctx {
-                HISTORICAL_SYNC_LOW
-                | HISTORICAL_SYNC_HIGH
-                | LAST_MC_BLOCK_ID
-                | INIT_MC_BLOCK_ID
-                | SHARDS_CLIENT_MC_BLOCK_ID => BlockIdFullRepr.repr_value(ctx, value),
-                LAST_UPLOADED_ARCHIVE => UintRepr::<u32>(PhantomData).repr_value(ctx, value),
-                DB_VERSION_KEY if value.len() != 3 => {
-                    format!("<invalid version {}>", hex::encode(value))
-                }
-                DB_VERSION_KEY => {
-                    format!("{}.{}.{}", value[0], value[1], value[2])
-                }
-                _ => hex::encode(value),
+pub struct CfIterator<'a> {
+    iter: rocksdb::DBIterator<'a>,
+    key_repr: &'static dyn ValueRepr,
+    value_repr: &'static dyn ValueRepr,
+}
+
+impl<'a> Iterator for CfIterator<'a> {
+    type Item = CfEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.iter.next()? {
+            Ok((key, value)) => {
+                let decoded_key = self.key_repr.decode(&key, &key);
+                let decoded_value = self.value_repr.decode(&key, &value);
+                Some(CfEntry {
+                    key,
+                    value,
+                    decoded_key,
+                    decoded_value,
+                })
             }
+            Err(_) => None,
         }
     }
 }
+
+pub struct CfEntry {
+    pub key: Box<[u8]>,
+    pub value: Box<[u8]>,
+    pub decoded_key: Value,
+    pub decoded_value: Value,
+}
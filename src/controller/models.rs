@@ -0,0 +1,355 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockIdShort {
+    pub workchain: i32,
+    pub shard: u64,
+    pub seqno: u32,
+}
+
+impl BlockIdShort {
+    pub const LEN: usize = 16;
+    /// Length of the workchain and shard part.
+    pub const PREFIX_LEN: usize = 12;
+
+    /// Reads the id from the first [`Self::LEN`] bytes.
+    pub fn read(data: &[u8]) -> Option<Self> {
+        if data.len() < Self::LEN {
+            return None;
+        }
+
+        Some(Self {
+            workchain: i32::from_be_bytes(data[0..4].try_into().unwrap()),
+            shard: u64::from_be_bytes(data[4..12].try_into().unwrap()),
+            seqno: u32::from_be_bytes(data[12..16].try_into().unwrap()),
+        })
+    }
+
+    pub fn to_bytes(self) -> [u8; Self::LEN] {
+        let mut result = [0; Self::LEN];
+        result[0..4].copy_from_slice(&self.workchain.to_be_bytes());
+        result[4..12].copy_from_slice(&self.shard.to_be_bytes());
+        result[12..16].copy_from_slice(&self.seqno.to_be_bytes());
+        result
+    }
+
+    /// Parses `workchain:shard:seqno` from the first three parts.
+    fn parse_parts<'a, I>(parts: &mut I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        let mut next = |name: &str| {
+            parts
+                .next()
+                .map(str::trim)
+                .context(format!("Missing {name}"))
+        };
+
+        let workchain = next("workchain")?.parse().context("Invalid workchain")?;
+        let shard = u64::from_str_radix(next("shard")?, 16).context("Invalid shard")?;
+        let seqno = next("seqno")?.parse().context("Invalid seqno")?;
+        Ok(Self {
+            workchain,
+            shard,
+            seqno,
+        })
+    }
+}
+
+impl fmt::Display for BlockIdShort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:2}:{:016x}:{}", self.workchain, self.shard, self.seqno)
+    }
+}
+
+impl FromStr for BlockIdShort {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split(':');
+        let id = Self::parse_parts(&mut parts)?;
+        expect_end(parts)?;
+        Ok(id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockIdFull {
+    pub workchain: i32,
+    pub shard: u64,
+    pub seqno: u32,
+    pub root_hash: [u8; 32],
+    pub file_hash: [u8; 32],
+}
+
+impl BlockIdFull {
+    pub const LEN: usize = BlockIdShort::LEN + 32 + 32;
+
+    /// Reads the id from the first [`Self::LEN`] bytes.
+    pub fn read(data: &[u8]) -> Option<Self> {
+        if data.len() < Self::LEN {
+            return None;
+        }
+
+        let short = BlockIdShort::read(data)?;
+        Some(Self {
+            workchain: short.workchain,
+            shard: short.shard,
+            seqno: short.seqno,
+            root_hash: data[16..48].try_into().unwrap(),
+            file_hash: data[48..80].try_into().unwrap(),
+        })
+    }
+
+    pub fn short(&self) -> BlockIdShort {
+        BlockIdShort {
+            workchain: self.workchain,
+            shard: self.shard,
+            seqno: self.seqno,
+        }
+    }
+
+    pub fn to_bytes(self) -> [u8; Self::LEN] {
+        let mut result = [0; Self::LEN];
+        result[0..16].copy_from_slice(&self.short().to_bytes());
+        result[16..48].copy_from_slice(&self.root_hash);
+        result[48..80].copy_from_slice(&self.file_hash);
+        result
+    }
+}
+
+impl fmt::Display for BlockIdFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.short(),
+            hex::encode(self.root_hash),
+            hex::encode(self.file_hash)
+        )
+    }
+}
+
+impl FromStr for BlockIdFull {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split(':');
+        let short = BlockIdShort::parse_parts(&mut parts)?;
+        let root_hash = parse_hash(parts.next(), "root hash")?;
+        let file_hash = parse_hash(parts.next(), "file hash")?;
+        expect_end(parts)?;
+
+        Ok(Self {
+            workchain: short.workchain,
+            shard: short.shard,
+            seqno: short.seqno,
+            root_hash,
+            file_hash,
+        })
+    }
+}
+
+/// Key of the `package_entries` column family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PackageEntryId {
+    pub block_id: BlockIdShort,
+    pub root_hash: [u8; 32],
+    pub package_type: PackageType,
+}
+
+impl PackageEntryId {
+    pub const LEN: usize = BlockIdShort::LEN + 32 + 1;
+
+    pub fn read(data: &[u8]) -> Option<Self> {
+        if data.len() != Self::LEN {
+            return None;
+        }
+
+        Some(Self {
+            block_id: BlockIdShort::read(data)?,
+            root_hash: data[16..48].try_into().unwrap(),
+            package_type: PackageType::from_u8(data[48]),
+        })
+    }
+
+    /// Common prefix of all package entries of the block.
+    pub fn block_prefix(id: &BlockIdFull) -> [u8; Self::LEN - 1] {
+        let mut result = [0; Self::LEN - 1];
+        result[0..16].copy_from_slice(&id.short().to_bytes());
+        result[16..48].copy_from_slice(&id.root_hash);
+        result
+    }
+
+    pub fn to_bytes(self) -> [u8; Self::LEN] {
+        let mut result = [0; Self::LEN];
+        result[0..16].copy_from_slice(&self.block_id.to_bytes());
+        result[16..48].copy_from_slice(&self.root_hash);
+        result[48] = self.package_type.to_u8();
+        result
+    }
+}
+
+impl fmt::Display for PackageEntryId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}",
+            self.block_id,
+            hex::encode(self.root_hash),
+            self.package_type
+        )
+    }
+}
+
+impl FromStr for PackageEntryId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split(':');
+        let block_id = BlockIdShort::parse_parts(&mut parts)?;
+        let root_hash = parse_hash(parts.next(), "root hash")?;
+        let package_type = parts.next().context("Missing package type")?.parse()?;
+        expect_end(parts)?;
+
+        Ok(Self {
+            block_id,
+            root_hash,
+            package_type,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PackageType {
+    Block,
+    Proof,
+    ProofLink,
+    Unknown(u8),
+}
+
+impl PackageType {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::Block,
+            1 => Self::Proof,
+            2 => Self::ProofLink,
+            other => Self::Unknown(other),
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::Block => 0,
+            Self::Proof => 1,
+            Self::ProofLink => 2,
+            Self::Unknown(value) => value,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Block => "block",
+            Self::Proof => "proof",
+            Self::ProofLink => "proof_link",
+            Self::Unknown(_) => "unknown",
+        }
+    }
+}
+
+/// Unknown types keep their tag, e.g. `unknown(7)`, so they can be parsed back.
+impl fmt::Display for PackageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(value) => write!(f, "unknown({value})"),
+            known => f.write_str(known.name()),
+        }
+    }
+}
+
+impl FromStr for PackageType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "block" => Ok(Self::Block),
+            "proof" => Ok(Self::Proof),
+            "proof_link" => Ok(Self::ProofLink),
+            other => {
+                let value = other
+                    .strip_prefix("unknown(")
+                    .and_then(|rest| rest.strip_suffix(')'))
+                    .and_then(|value| value.parse().ok())
+                    .with_context(|| format!("Unknown package type: {other}"))?;
+                Ok(Self::from_u8(value))
+            }
+        }
+    }
+}
+
+/// Value of the `block_handles` column family, keyed by the block root hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockMeta {
+    pub flags: u32,
+    /// Seqno of the masterchain block which references the block.
+    pub mc_ref_seqno: u32,
+    pub gen_utime: u32,
+}
+
+impl BlockMeta {
+    pub const LEN: usize = 12;
+
+    /// Flag names by bit, bit 3 is unused.
+    pub const FLAGS: [&'static str; 14] = [
+        "has_data",
+        "has_proof",
+        "has_proof_link",
+        "",
+        "has_state",
+        "has_persistent_state",
+        "has_next1",
+        "has_next2",
+        "has_prev1",
+        "has_prev2",
+        "applied",
+        "key_block",
+        "archived",
+        "indexed",
+    ];
+
+    /// Flags, the masterchain ref seqno and the time are little-endian u32s.
+    pub fn read(data: &[u8]) -> Option<Self> {
+        if data.len() != Self::LEN {
+            return None;
+        }
+
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+        Some(Self {
+            flags: u32_at(0),
+            mc_ref_seqno: u32_at(4),
+            gen_utime: u32_at(8),
+        })
+    }
+}
+
+pub(super) fn parse_hash(part: Option<&str>, name: &str) -> Result<[u8; 32]> {
+    let part = part.map(str::trim).context(format!("Missing {name}"))?;
+
+    let mut hash = [0; 32];
+    hex::decode_to_slice(part, &mut hash).context(format!("Invalid {name}"))?;
+    Ok(hash)
+}
+
+fn expect_end<'a, I>(mut parts: I) -> Result<()>
+where
+    I: Iterator<Item = &'a str>,
+{
+    match parts.next() {
+        Some(_) => anyhow::bail!("Unexpected trailing data"),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,68 @@
+use anyhow::{Context, Result};
+
+use super::models::{BlockIdFull, BlockIdShort};
+
+/// Sync state of a ton-indexer node from `node_states`, with the newest blocks the DB has.
+pub struct NodeState {
+    pub init_mc_block: Option<BlockIdFull>,
+    pub shards_client_mc_block: Option<BlockIdFull>,
+    pub last_mc_block: Option<BlockIdFull>,
+    pub background_sync_low: Option<BlockIdFull>,
+    pub background_sync_high: Option<BlockIdFull>,
+    pub last_uploaded_archive: Option<u32>,
+    pub db_version: Option<[u8; 3]>,
+    /// Highest seqno in `key_blocks`.
+    pub last_key_block_seqno: Option<u32>,
+    /// Highest masterchain seqno in `shard_states`.
+    pub last_mc_state_seqno: Option<u32>,
+}
+
+/// Reads the node state, or returns `None` if the DB has no `node_states`.
+pub(super) fn read(db: &rocksdb::DB) -> Result<Option<NodeState>> {
+    let Some(handle) = db.cf_handle("node_states") else {
+        return Ok(None);
+    };
+    let get = |key: &str| {
+        db.get_cf(&handle, key)
+            .with_context(|| format!("Failed to read {key}"))
+    };
+    let block_id =
+        |key: &str| Ok::<_, anyhow::Error>(get(key)?.as_deref().and_then(BlockIdFull::read));
+
+    let last_uploaded_archive = get("last_uploaded_archive")?
+        .and_then(|value| Some(u32::from_be_bytes(value.as_slice().try_into().ok()?)));
+    let db_version = get("db_version")?.and_then(|value| value.as_slice().try_into().ok());
+
+    // Keys are big endian, so the last ones have the highest seqnos
+    let last_key = |cf_name: &str| -> Result<Option<Box<[u8]>>> {
+        let Some(handle) = db.cf_handle(cf_name) else {
+            return Ok(None);
+        };
+        match db.iterator_cf(&handle, rocksdb::IteratorMode::End).next() {
+            Some(item) => Ok(Some(
+                item.with_context(|| format!("Failed to read {cf_name}"))?.0,
+            )),
+            None => Ok(None),
+        }
+    };
+    let last_key_block_seqno = last_key("key_blocks")?
+        .and_then(|key| Some(u32::from_be_bytes(key.get(..4)?.try_into().unwrap())));
+    // The masterchain workchain -1 sorts after all others
+    let last_mc_state_seqno = last_key("shard_states")?
+        .as_deref()
+        .and_then(BlockIdShort::read)
+        .filter(|id| id.workchain == -1)
+        .map(|id| id.seqno);
+
+    Ok(Some(NodeState {
+        init_mc_block: block_id("InitMcBlockId")?,
+        shards_client_mc_block: block_id("ShardsClientMcBlockId")?,
+        last_mc_block: block_id("LastMcBlockId")?,
+        background_sync_low: block_id("background_sync_low")?,
+        background_sync_high: block_id("background_sync_high")?,
+        last_uploaded_archive,
+        db_version,
+        last_key_block_seqno,
+        last_mc_state_seqno,
+    }))
+}
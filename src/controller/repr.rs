@@ -0,0 +1,909 @@
+use std::marker::PhantomData;
+
+use anyhow::{Context, Result};
+use base64::Engine as _;
+
+use super::archive::ArchivePackage;
+use super::boc::Boc;
+use super::cell_storage::StoredCell;
+use super::models::{parse_hash, BlockIdFull, BlockIdShort, BlockMeta, PackageEntryId};
+use super::value::{Value, ValueKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReprKind {
+    Hex,
+    Utf8,
+    Base64,
+    U16Be,
+    U16Le,
+    U32Be,
+    U32Le,
+    U64Be,
+    U64Le,
+    I64Be,
+    I64Le,
+    Varint,
+    UnixTime,
+    UnixTime64,
+    Blob,
+    BlockIdShort,
+    BlockIdFull,
+    ShardState,
+    PackageEntryId,
+    NodeStates,
+    ArchivePackage,
+    Boc,
+    StoredCell,
+    BlockMeta,
+}
+
+impl ReprKind {
+    pub const ALL: [Self; 24] = [
+        Self::Hex,
+        Self::Utf8,
+        Self::Base64,
+        Self::U16Be,
+        Self::U16Le,
+        Self::U32Be,
+        Self::U32Le,
+        Self::U64Be,
+        Self::U64Le,
+        Self::I64Be,
+        Self::I64Le,
+        Self::Varint,
+        Self::UnixTime,
+        Self::UnixTime64,
+        Self::Blob,
+        Self::BlockIdShort,
+        Self::BlockIdFull,
+        Self::ShardState,
+        Self::PackageEntryId,
+        Self::NodeStates,
+        Self::ArchivePackage,
+        Self::Boc,
+        Self::StoredCell,
+        Self::BlockMeta,
+    ];
+
+    /// Stable identifier used to persist the choice.
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Hex => "hex",
+            Self::Utf8 => "utf8",
+            Self::Base64 => "base64",
+            Self::U16Be => "u16_be",
+            Self::U16Le => "u16_le",
+            Self::U32Be => "u32_be",
+            Self::U32Le => "u32_le",
+            Self::U64Be => "u64_be",
+            Self::U64Le => "u64_le",
+            Self::I64Be => "i64_be",
+            Self::I64Le => "i64_le",
+            Self::Varint => "varint",
+            Self::UnixTime => "unix_time",
+            Self::UnixTime64 => "unix_time64",
+            Self::Blob => "blob",
+            Self::BlockIdShort => "block_id_short",
+            Self::BlockIdFull => "block_id_full",
+            Self::ShardState => "shard_state",
+            Self::PackageEntryId => "package_entry_id",
+            Self::NodeStates => "node_states",
+            Self::ArchivePackage => "archive_package",
+            Self::Boc => "boc",
+            Self::StoredCell => "stored_cell",
+            Self::BlockMeta => "block_meta",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.id() == id)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hex => "Hex",
+            Self::Utf8 => "UTF-8",
+            Self::Base64 => "Base64",
+            Self::U16Be => "u16 (big-endian)",
+            Self::U16Le => "u16 (little-endian)",
+            Self::U32Be => "u32 (big-endian)",
+            Self::U32Le => "u32 (little-endian)",
+            Self::U64Be => "u64 (big-endian)",
+            Self::U64Le => "u64 (little-endian)",
+            Self::I64Be => "i64 (big-endian)",
+            Self::I64Le => "i64 (little-endian)",
+            Self::Varint => "Varint",
+            Self::UnixTime => "Unix timestamp (u32)",
+            Self::UnixTime64 => "Unix timestamp (u64)",
+            Self::Blob => "Size only",
+            Self::BlockIdShort => "Block id (short)",
+            Self::BlockIdFull => "Block id (full)",
+            Self::ShardState => "Shard state",
+            Self::PackageEntryId => "Package entry id",
+            Self::NodeStates => "Node states",
+            Self::ArchivePackage => "Archive package",
+            Self::Boc => "Bag of cells",
+            Self::StoredCell => "Stored cell",
+            Self::BlockMeta => "Block meta",
+        }
+    }
+
+    pub fn decode(self, ctx: &[u8], value: &[u8]) -> Value {
+        self.repr().decode(ctx, value)
+    }
+
+    /// Whether the encoded data starts with a [`BlockIdShort`], so it is sorted by block id.
+    pub fn has_block_id_prefix(self) -> bool {
+        matches!(
+            self,
+            Self::BlockIdShort | Self::BlockIdFull | Self::PackageEntryId
+        )
+    }
+
+    /// Frequently used filters on values of this kind, as labels and expressions.
+    pub fn filter_presets(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::BlockMeta => &[
+                (
+                    "Has data, not applied",
+                    "value.flags.has_data && !value.flags.applied",
+                ),
+                (
+                    "Applied, not archived",
+                    "value.flags.applied && !value.flags.archived",
+                ),
+                ("Key blocks", "value.flags.key_block"),
+            ],
+            _ => &[],
+        }
+    }
+
+    /// Encodes text in the displayed form back into raw bytes.
+    ///
+    /// Text prefixed with `0x` is always treated as raw hex, and text prefixed
+    /// with a decoder id and a colon (e.g. `base64:AAAA`) is encoded with that decoder.
+    pub fn encode(self, text: &str) -> Result<Vec<u8>> {
+        let text = text.trim();
+        if let Some(data) = text.strip_prefix("0x") {
+            return hex::decode(data).context("Invalid hex");
+        }
+
+        match text
+            .split_once(':')
+            .and_then(|(id, rest)| Some((Self::from_id(id)?, rest)))
+        {
+            Some((kind, rest)) => kind.repr().encode(rest.trim()),
+            None => self.repr().encode(text),
+        }
+    }
+
+    pub(super) fn repr(self) -> &'static dyn ValueRepr {
+        match self {
+            Self::Hex => &HexRepr,
+            Self::Utf8 => &Utf8Repr,
+            Self::Base64 => &Base64Repr,
+            Self::U16Be => &UintRepr::<u16>(PhantomData),
+            Self::U16Le => &UintLeRepr::<u16>(PhantomData),
+            Self::U32Be => &UintRepr::<u32>(PhantomData),
+            Self::U32Le => &UintLeRepr::<u32>(PhantomData),
+            Self::U64Be => &UintRepr::<u64>(PhantomData),
+            Self::U64Le => &UintLeRepr::<u64>(PhantomData),
+            Self::I64Be => &IntRepr::<i64>(PhantomData),
+            Self::I64Le => &IntLeRepr::<i64>(PhantomData),
+            Self::Varint => &VarintRepr,
+            Self::UnixTime => &UnixTimeRepr::<u32>(PhantomData),
+            Self::UnixTime64 => &UnixTimeRepr::<u64>(PhantomData),
+            Self::Blob => &BlobRepr,
+            Self::BlockIdShort => &BlockIdShortRepr,
+            Self::BlockIdFull => &BlockIdFullRepr,
+            Self::ShardState => &ShardStateRepr,
+            Self::PackageEntryId => &PackageEntryIdRepr,
+            Self::NodeStates => &NodeStatesRepr,
+            Self::ArchivePackage => &ArchivePackageRepr,
+            Self::Boc => &BocRepr,
+            Self::StoredCell => &StoredCellRepr,
+            Self::BlockMeta => &BlockMetaRepr,
+        }
+    }
+}
+
+pub trait ValueRepr {
+    /// Decodes `value`, with `ctx` being the key of the entry.
+    fn decode(&self, ctx: &[u8], value: &[u8]) -> Value;
+
+    /// Inverse of [`ValueRepr::decode`] for the displayed text.
+    fn encode(&self, text: &str) -> Result<Vec<u8>>;
+}
+
+struct UintRepr<T>(PhantomData<T>);
+
+struct UintLeRepr<T>(PhantomData<T>);
+
+struct IntRepr<T>(PhantomData<T>);
+
+struct IntLeRepr<T>(PhantomData<T>);
+
+macro_rules! impl_int_repr {
+    ($repr:ident, $from_bytes:ident, $to_bytes:ident, $kind:ident, [$($ty:ty),*]) => {$(
+        impl ValueRepr for $repr<$ty> {
+            fn decode(&self, _: &[u8], value: &[u8]) -> Value {
+                match value.try_into() {
+                    Ok(bytes) => Value::new(
+                        0..value.len(),
+                        ValueKind::$kind(<$ty>::$from_bytes(bytes).into()),
+                    ),
+                    Err(_) => Value::invalid_len(value, std::mem::size_of::<$ty>()),
+                }
+            }
+
+            fn encode(&self, text: &str) -> Result<Vec<u8>> {
+                let number = text
+                    .parse::<$ty>()
+                    .context(concat!("Invalid ", stringify!($ty)))?;
+                Ok(number.$to_bytes().to_vec())
+            }
+        }
+    )*};
+}
+
+impl_int_repr!(UintRepr, from_be_bytes, to_be_bytes, Uint, [u16, u32, u64]);
+impl_int_repr!(
+    UintLeRepr,
+    from_le_bytes,
+    to_le_bytes,
+    Uint,
+    [u16, u32, u64]
+);
+impl_int_repr!(IntRepr, from_be_bytes, to_be_bytes, Int, [i64]);
+impl_int_repr!(IntLeRepr, from_le_bytes, to_le_bytes, Int, [i64]);
+
+struct HexRepr;
+
+impl ValueRepr for HexRepr {
+    fn decode(&self, _: &[u8], value: &[u8]) -> Value {
+        Value::new(0..value.len(), ValueKind::Bytes(value.to_vec()))
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        hex::decode(text).context("Invalid hex")
+    }
+}
+
+struct Utf8Repr;
+
+impl ValueRepr for Utf8Repr {
+    fn decode(&self, _: &[u8], value: &[u8]) -> Value {
+        let text = String::from_utf8_lossy(value).escape_debug().to_string();
+        Value::new(0..value.len(), ValueKind::Str(text))
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        unescape(text).map(String::into_bytes)
+    }
+}
+
+/// Reverts [`str::escape_debug`].
+fn unescape(text: &str) -> Result<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        result.push(match chars.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some(c @ ('\\' | '\'' | '"')) => c,
+            Some('u') => chars
+                .next()
+                .filter(|c| *c == '{')
+                .and_then(|_| {
+                    let code = chars.by_ref().take_while(|c| *c != '}').collect::<String>();
+                    u32::from_str_radix(&code, 16).ok()
+                })
+                .and_then(char::from_u32)
+                .context("Invalid unicode escape")?,
+            _ => anyhow::bail!("Invalid escape sequence"),
+        });
+    }
+    Ok(result)
+}
+
+struct Base64Repr;
+
+impl ValueRepr for Base64Repr {
+    fn decode(&self, _: &[u8], value: &[u8]) -> Value {
+        let text = base64::engine::general_purpose::STANDARD.encode(value);
+        Value::new(0..value.len(), ValueKind::Str(text))
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        base64::engine::general_purpose::STANDARD
+            .decode(text)
+            .context("Invalid base64")
+    }
+}
+
+/// LEB128-encoded unsigned integer.
+struct VarintRepr;
+
+impl ValueRepr for VarintRepr {
+    fn decode(&self, _: &[u8], value: &[u8]) -> Value {
+        match read_varint(value) {
+            Some((number, len)) if len == value.len() => {
+                Value::new(0..len, ValueKind::Uint(number))
+            }
+            Some((_, len)) => Value::error(0..value.len(), len, "trailing bytes after varint"),
+            None => Value::error(0..value.len(), value.len(), "unterminated varint"),
+        }
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        let mut number = text.parse::<u64>().context("Invalid varint")?;
+
+        let mut result = Vec::new();
+        loop {
+            let byte = (number & 0x7f) as u8;
+            number >>= 7;
+            if number == 0 {
+                result.push(byte);
+                return Ok(result);
+            }
+            result.push(byte | 0x80);
+        }
+    }
+}
+
+fn read_varint(data: &[u8]) -> Option<(u64, usize)> {
+    let mut result = 0u64;
+    for (i, byte) in data.iter().take(10).enumerate() {
+        result |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((result, i + 1));
+        }
+    }
+    None
+}
+
+/// Seconds since the unix epoch, stored as a big-endian `u32` or `u64`.
+struct UnixTimeRepr<T>(PhantomData<T>);
+
+macro_rules! impl_unix_time_repr {
+    ($($ty:ty),*) => {$(
+        impl ValueRepr for UnixTimeRepr<$ty> {
+            fn decode(&self, _: &[u8], value: &[u8]) -> Value {
+                match value.try_into() {
+                    Ok(bytes) => Value::new(
+                        0..value.len(),
+                        ValueKind::Time(<$ty>::from_be_bytes(bytes).into()),
+                    ),
+                    Err(_) => Value::invalid_len(value, std::mem::size_of::<$ty>()),
+                }
+            }
+
+            /// Accepts either seconds or `YYYY-MM-DD HH:MM:SS [UTC]`.
+            fn encode(&self, text: &str) -> Result<Vec<u8>> {
+                let secs = match text.parse::<u64>() {
+                    Ok(secs) => secs,
+                    Err(_) => parse_unix_time(text).context("Invalid timestamp")?,
+                };
+                let secs = <$ty>::try_from(secs)
+                    .context(concat!("Timestamp doesn't fit ", stringify!($ty)))?;
+                Ok(secs.to_be_bytes().to_vec())
+            }
+        }
+    )*};
+}
+
+impl_unix_time_repr!(u32, u64);
+
+/// Parses `YYYY-MM-DD [HH:MM:SS] [UTC]` with a year up to 9999.
+fn parse_unix_time(text: &str) -> Option<u64> {
+    const YEARS: std::ops::RangeInclusive<i64> = 1970..=9999;
+
+    let text = text.trim_end_matches("UTC").trim();
+    let (date, time) = text.split_once(' ').unwrap_or((text, "00:00:00"));
+
+    let mut date = date.split('-').map(str::parse::<i64>);
+    let (Some(Ok(year)), Some(Ok(month)), Some(Ok(day)), None) =
+        (date.next(), date.next(), date.next(), date.next())
+    else {
+        return None;
+    };
+
+    let mut time = time.trim().split(':').map(str::parse::<u64>);
+    let (Some(Ok(hours)), Some(Ok(minutes)), Some(Ok(seconds)), None) =
+        (time.next(), time.next(), time.next(), time.next())
+    else {
+        return None;
+    };
+
+    let valid_date = YEARS.contains(&year)
+        && (1..=12).contains(&month)
+        && (1..=days_in_month(year, month)).contains(&day);
+    if !valid_date || hours >= 24 || minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+
+    // Days since epoch from a civil date, inverse of the formatting in `value.rs`
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era.checked_mul(146097)?.checked_add(doe - 719468)?;
+    let days = u64::try_from(days).ok()?;
+
+    days.checked_mul(86400)?
+        .checked_add(hours * 3600 + minutes * 60 + seconds)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+struct BlockIdShortRepr;
+
+impl ValueRepr for BlockIdShortRepr {
+    fn decode(&self, _: &[u8], value: &[u8]) -> Value {
+        if value.len() != BlockIdShort::LEN {
+            return Value::invalid_len(value, BlockIdShort::LEN);
+        }
+
+        let id = BlockIdShort::read(value).unwrap();
+        Value::new(0..value.len(), ValueKind::BlockIdShort(id))
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        Ok(text.parse::<BlockIdShort>()?.to_bytes().to_vec())
+    }
+}
+
+struct BlockIdFullRepr;
+
+impl ValueRepr for BlockIdFullRepr {
+    fn decode(&self, _: &[u8], value: &[u8]) -> Value {
+        if value.len() != BlockIdFull::LEN {
+            return Value::invalid_len(value, BlockIdFull::LEN);
+        }
+
+        let id = BlockIdFull::read(value).unwrap();
+        Value::new(0..value.len(), ValueKind::BlockIdFull(id))
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        Ok(text.parse::<BlockIdFull>()?.to_bytes().to_vec())
+    }
+}
+
+struct ShardStateRepr;
+
+impl ValueRepr for ShardStateRepr {
+    fn decode(&self, _: &[u8], value: &[u8]) -> Value {
+        if value.len() != 32 * 3 {
+            return Value::invalid_len(value, 32 * 3);
+        }
+
+        let hash = |name, offset: usize| {
+            let range = offset..offset + 32;
+            let bytes = value[range.clone()].to_vec();
+            (name, Value::new(range, ValueKind::Bytes(bytes)))
+        };
+
+        Value::new(
+            0..value.len(),
+            ValueKind::Struct(vec![
+                hash("state_root", 0),
+                hash("root_hash", 32),
+                hash("file_hash", 64),
+            ]),
+        )
+    }
+
+    /// Accepts `state_root: …, root_hash: …, file_hash: …`.
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        let mut result = Vec::with_capacity(32 * 3);
+        let mut parts = text.split(',');
+        for name in ["state_root", "root_hash", "file_hash"] {
+            let part = parts.next().context(format!("Missing {name}"))?;
+            let hash = match part.split_once(':') {
+                Some((field, hash)) if field.trim() == name => hash,
+                Some(_) => anyhow::bail!("Expected {name}"),
+                None => part,
+            };
+            result.extend_from_slice(&parse_hash(Some(hash), name)?);
+        }
+        anyhow::ensure!(parts.next().is_none(), "Unexpected trailing data");
+        Ok(result)
+    }
+}
+
+struct BlobRepr;
+
+impl ValueRepr for BlobRepr {
+    fn decode(&self, _: &[u8], value: &[u8]) -> Value {
+        Value::new(0..value.len(), ValueKind::Blob(value.len()))
+    }
+
+    fn encode(&self, _: &str) -> Result<Vec<u8>> {
+        anyhow::bail!("Values shown as size only can't be encoded, use 0x-prefixed hex")
+    }
+}
+
+struct PackageEntryIdRepr;
+
+impl ValueRepr for PackageEntryIdRepr {
+    fn decode(&self, _: &[u8], value: &[u8]) -> Value {
+        match PackageEntryId::read(value) {
+            Some(id) => Value::new(0..value.len(), ValueKind::PackageEntryId(id)),
+            None => Value::invalid_len(value, PackageEntryId::LEN),
+        }
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        Ok(text.parse::<PackageEntryId>()?.to_bytes().to_vec())
+    }
+}
+
+struct ArchivePackageRepr;
+
+impl ValueRepr for ArchivePackageRepr {
+    fn decode(&self, _: &[u8], value: &[u8]) -> Value {
+        let package = ArchivePackage::parse(value);
+
+        let mut items = package
+            .entries
+            .into_iter()
+            .map(|entry| {
+                let (package_type, block_id) = match entry.id {
+                    Some((package_type, id)) => (
+                        ValueKind::Str(package_type.name().to_owned()),
+                        ValueKind::BlockIdFull(id),
+                    ),
+                    None => (
+                        ValueKind::Str("unknown".to_owned()),
+                        ValueKind::Str(entry.name.clone()),
+                    ),
+                };
+
+                let fields = vec![
+                    ("type", Value::new(entry.name_range.clone(), package_type)),
+                    ("block_id", Value::new(entry.name_range.clone(), block_id)),
+                    (
+                        "offset",
+                        Value::new(
+                            entry.header.clone(),
+                            ValueKind::Uint(entry.data.start as u64),
+                        ),
+                    ),
+                    (
+                        "size",
+                        Value::new(
+                            entry.header.start + 4..entry.header.end,
+                            ValueKind::Uint(entry.data.len() as u64),
+                        ),
+                    ),
+                ];
+                (
+                    entry.name,
+                    Value::new(entry.data, ValueKind::Struct(fields)),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if let Some((offset, reason)) = package.error {
+            items.push((
+                "error".to_owned(),
+                Value::error(offset..value.len(), offset, reason),
+            ));
+        }
+
+        Value::new(0..value.len(), ValueKind::List(items))
+    }
+
+    fn encode(&self, _: &str) -> Result<Vec<u8>> {
+        anyhow::bail!("Archive packages can't be encoded, use 0x-prefixed hex")
+    }
+}
+
+struct BocRepr;
+
+impl ValueRepr for BocRepr {
+    fn decode(&self, _: &[u8], value: &[u8]) -> Value {
+        let boc = match Boc::parse(value) {
+            Ok(boc) => boc,
+            Err((offset, reason)) => return Value::error(0..value.len(), offset, reason),
+        };
+
+        boc.summary(value)
+    }
+
+    fn encode(&self, _: &str) -> Result<Vec<u8>> {
+        anyhow::bail!("Bags of cells can't be encoded, use 0x-prefixed hex")
+    }
+}
+
+struct StoredCellRepr;
+
+impl ValueRepr for StoredCellRepr {
+    fn decode(&self, _: &[u8], value: &[u8]) -> Value {
+        let cell = match StoredCell::parse(value) {
+            Ok(cell) => cell,
+            Err((offset, reason)) => return Value::error(0..value.len(), offset, reason),
+        };
+
+        let descriptor = 8..12;
+        let repr_hash = cell.hashes.last().unwrap().clone();
+        let refs = cell
+            .refs
+            .iter()
+            .enumerate()
+            .map(|(i, range)| {
+                let hash = value[range.clone()].to_vec();
+                (
+                    i.to_string(),
+                    Value::new(range.clone(), ValueKind::Bytes(hash)),
+                )
+            })
+            .collect::<Vec<_>>();
+        let refs_range = match (cell.refs.first(), cell.refs.last()) {
+            (Some(first), Some(last)) => first.start..last.end,
+            _ => value.len()..value.len(),
+        };
+
+        Value::new(
+            0..value.len(),
+            ValueKind::Struct(vec![
+                ("refcount", Value::new(0..8, ValueKind::Int(cell.refcount))),
+                (
+                    "type",
+                    Value::new(
+                        descriptor.clone(),
+                        ValueKind::Str(cell.cell_type.name().to_owned()),
+                    ),
+                ),
+                (
+                    "level_mask",
+                    Value::new(descriptor.clone(), ValueKind::Uint(cell.level_mask as u64)),
+                ),
+                (
+                    "bits",
+                    Value::new(descriptor, ValueKind::Uint(cell.bit_len as u64)),
+                ),
+                (
+                    "data",
+                    Value::new(
+                        cell.data.clone(),
+                        ValueKind::Bytes(value[cell.data.clone()].to_vec()),
+                    ),
+                ),
+                (
+                    "hash",
+                    Value::new(
+                        repr_hash.clone(),
+                        ValueKind::Bytes(cell.repr_hash().to_vec()),
+                    ),
+                ),
+                (
+                    "depth",
+                    Value::new(repr_hash, ValueKind::Uint(cell.repr_depth() as u64)),
+                ),
+                ("refs", Value::new(refs_range, ValueKind::List(refs))),
+            ]),
+        )
+    }
+
+    fn encode(&self, _: &str) -> Result<Vec<u8>> {
+        anyhow::bail!("Stored cells can't be encoded, use 0x-prefixed hex")
+    }
+}
+
+struct BlockMetaRepr;
+
+impl ValueRepr for BlockMetaRepr {
+    fn decode(&self, _: &[u8], value: &[u8]) -> Value {
+        let Some(meta) = BlockMeta::read(value) else {
+            return Value::invalid_len(value, BlockMeta::LEN);
+        };
+
+        let flags = ValueKind::Flags {
+            bits: meta.flags as u64,
+            names: &BlockMeta::FLAGS,
+        };
+        Value::new(
+            0..value.len(),
+            ValueKind::Struct(vec![
+                ("flags", Value::new(0..4, flags)),
+                (
+                    "mc_ref_seqno",
+                    Value::new(4..8, ValueKind::Uint(meta.mc_ref_seqno as u64)),
+                ),
+                (
+                    "gen_utime",
+                    Value::new(8..12, ValueKind::Time(meta.gen_utime as u64)),
+                ),
+            ]),
+        )
+    }
+
+    fn encode(&self, _: &str) -> Result<Vec<u8>> {
+        anyhow::bail!("Block meta can't be encoded, use 0x-prefixed hex")
+    }
+}
+
+struct NodeStatesRepr;
+
+impl NodeStatesRepr {
+    const HISTORICAL_SYNC_LOW: &[u8] = b"background_sync_low";
+    const HISTORICAL_SYNC_HIGH: &[u8] = b"background_sync_high";
+
+    const LAST_UPLOADED_ARCHIVE: &[u8] = b"last_uploaded_archive";
+
+    const LAST_MC_BLOCK_ID: &[u8] = b"LastMcBlockId";
+    const INIT_MC_BLOCK_ID: &[u8] = b"InitMcBlockId";
+    const SHARDS_CLIENT_MC_BLOCK_ID: &[u8] = b"ShardsClientMcBlockId";
+
+    const DB_VERSION_KEY: &[u8] = b"db_version";
+}
+
+impl ValueRepr for NodeStatesRepr {
+    fn decode(&self, ctx: &[u8], value: &[u8]) -> Value {
+        if ctx == value {
+            let text = String::from_utf8_lossy(value).to_string();
+            return Value::new(0..value.len(), ValueKind::Str(text));
+        }
+
+        match ctx {
+            Self::HISTORICAL_SYNC_LOW
+            | Self::HISTORICAL_SYNC_HIGH
+            | Self::LAST_MC_BLOCK_ID
+            | Self::INIT_MC_BLOCK_ID
+            | Self::SHARDS_CLIENT_MC_BLOCK_ID => BlockIdFullRepr.decode(ctx, value),
+            Self::LAST_UPLOADED_ARCHIVE => UintRepr::<u32>(PhantomData).decode(ctx, value),
+            Self::DB_VERSION_KEY if value.len() != 3 => Value::invalid_len(value, 3),
+            Self::DB_VERSION_KEY => {
+                let version = format!("{}.{}.{}", value[0], value[1], value[2]);
+                Value::new(0..value.len(), ValueKind::Str(version))
+            }
+            _ => HexRepr.decode(ctx, value),
+        }
+    }
+
+    /// Only keys can be encoded since values depend on the key.
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        Ok(text.as_bytes().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::models::PackageType;
+
+    /// Encodes the displayed text of the decoded value back.
+    fn round_trip(kind: ReprKind, data: &[u8]) -> Vec<u8> {
+        let text = kind.decode(data, data).to_string();
+        kind.encode(&text)
+            .unwrap_or_else(|e| panic!("{} failed to encode {text:?}: {e:?}", kind.id()))
+    }
+
+    #[test]
+    fn invertible_reprs() {
+        let block_id = BlockIdFull {
+            workchain: -1,
+            shard: 0x8000000000000000,
+            seqno: 123,
+            root_hash: [0x11; 32],
+            file_hash: [0x22; 32],
+        };
+        let package_entry = PackageEntryId {
+            block_id: BlockIdShort {
+                workchain: 0,
+                shard: 0x4000000000000000,
+                seqno: 7,
+            },
+            root_hash: [0x33; 32],
+            package_type: PackageType::ProofLink,
+        };
+        let unknown_entry = PackageEntryId {
+            package_type: PackageType::Unknown(7),
+            ..package_entry
+        };
+        let shard_state = [[0x44; 32], [0x55; 32], [0x66; 32]].concat();
+        // 2200-01-01, which doesn't fit u32
+        let late_time = 7258118400u64.to_be_bytes();
+        let early_time = 1700000000u64.to_be_bytes();
+
+        let samples: [(ReprKind, &[u8]); 23] = [
+            (ReprKind::Hex, &[0xde, 0xad, 0xbe, 0xef]),
+            (ReprKind::Hex, &[]),
+            (ReprKind::Utf8, "a \"quoted\"\n\ttext\\ ü\u{1}".as_bytes()),
+            (ReprKind::Base64, &[0xff, 0x00, 0x7f, 0x80, 0x01]),
+            (ReprKind::U16Be, &[0x12, 0x34]),
+            (ReprKind::U16Le, &[0x12, 0x34]),
+            (ReprKind::U32Be, &[0x12, 0x34, 0x56, 0x78]),
+            (ReprKind::U32Le, &[0x12, 0x34, 0x56, 0x78]),
+            (ReprKind::U64Be, &u64::MAX.to_be_bytes()),
+            (ReprKind::U64Le, &1u64.to_le_bytes()),
+            (ReprKind::I64Be, &(-42i64).to_be_bytes()),
+            (ReprKind::I64Le, &i64::MIN.to_le_bytes()),
+            (ReprKind::Varint, &[0xac, 0x02]),
+            (ReprKind::Varint, &[0x00]),
+            (ReprKind::UnixTime, &1700000000u32.to_be_bytes()),
+            (ReprKind::UnixTime64, &late_time),
+            (ReprKind::UnixTime64, &early_time),
+            (ReprKind::BlockIdShort, &block_id.short().to_bytes()),
+            (ReprKind::BlockIdFull, &block_id.to_bytes()),
+            (ReprKind::ShardState, &shard_state),
+            (ReprKind::PackageEntryId, &package_entry.to_bytes()),
+            (ReprKind::PackageEntryId, &unknown_entry.to_bytes()),
+            (ReprKind::NodeStates, b"LastMcBlockId"),
+        ];
+        for (kind, data) in samples {
+            assert_eq!(round_trip(kind, data), data, "{}", kind.id());
+        }
+    }
+
+    #[test]
+    fn not_encodable() {
+        for kind in [
+            ReprKind::Blob,
+            ReprKind::ArchivePackage,
+            ReprKind::Boc,
+            ReprKind::StoredCell,
+            ReprKind::BlockMeta,
+        ] {
+            assert!(kind.encode("anything").is_err(), "{}", kind.id());
+        }
+    }
+
+    #[test]
+    fn encode_prefixes() {
+        assert_eq!(ReprKind::Utf8.encode("0xdead").unwrap(), [0xde, 0xad]);
+        assert_eq!(ReprKind::Hex.encode("u32_be: 258").unwrap(), [0, 0, 1, 2]);
+        assert_eq!(ReprKind::Hex.encode("base64:AQI=").unwrap(), [1, 2]);
+        assert!(ReprKind::U16Be.encode("65536").is_err());
+        assert!(ReprKind::Varint.encode("-1").is_err());
+    }
+
+    #[test]
+    fn unix_time() {
+        assert_eq!(parse_unix_time("1970-01-01 00:00:00 UTC"), Some(0));
+        assert_eq!(parse_unix_time("2000-02-29"), Some(951782400));
+        assert_eq!(parse_unix_time("2023-11-14 22:13:20"), Some(1700000000));
+        assert_eq!(parse_unix_time("1969-12-31 23:59:59"), None);
+        assert_eq!(parse_unix_time("2023-13-01 00:00:00"), None);
+        assert_eq!(parse_unix_time("2023-11-14 22:13"), None);
+        assert_eq!(parse_unix_time("yesterday"), None);
+
+        assert_eq!(parse_unix_time("2023-02-31"), None);
+        assert_eq!(parse_unix_time("2023-02-29"), None);
+        assert_eq!(parse_unix_time("2023-01-01 24:00:00"), None);
+        assert_eq!(parse_unix_time("2023-01-01 00:60:00"), None);
+        assert_eq!(parse_unix_time("2023-01-01 99999999999999999:00:00"), None);
+        assert_eq!(parse_unix_time("9223372036854775807-01-01"), None);
+        assert_eq!(parse_unix_time("9999-12-31 23:59:59"), Some(253402300799));
+
+        // The width of the repr is kept
+        assert_eq!(
+            ReprKind::UnixTime
+                .encode("2023-11-14 22:13:20 UTC")
+                .unwrap(),
+            1700000000u32.to_be_bytes()
+        );
+        assert_eq!(
+            ReprKind::UnixTime64.encode("1700000000").unwrap(),
+            1700000000u64.to_be_bytes()
+        );
+        assert!(ReprKind::UnixTime.encode("2200-01-01").is_err());
+    }
+}
@@ -0,0 +1,227 @@
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+use super::repr::ValueRepr;
+use super::task::{scan_cf, Task};
+use super::{CfEntry, Filter, ReprKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    Substring,
+    Regex,
+    /// Hex bytes with `??` wildcards, e.g. `de ad ?? ef`.
+    Bytes,
+}
+
+impl SearchMode {
+    pub const ALL: [Self; 3] = [Self::Substring, Self::Regex, Self::Bytes];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Substring => "Substring",
+            Self::Regex => "Regex",
+            Self::Bytes => "Byte pattern",
+        }
+    }
+}
+
+/// What the pattern is matched against, for both keys and values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchTarget {
+    Raw,
+    Hex,
+    Decoded,
+}
+
+impl SearchTarget {
+    pub const ALL: [Self; 3] = [Self::Raw, Self::Hex, Self::Decoded];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Raw => "Raw bytes",
+            Self::Hex => "Hex",
+            Self::Decoded => "Decoded",
+        }
+    }
+}
+
+pub struct SearchQuery {
+    regex: regex::bytes::Regex,
+    target: SearchTarget,
+    filter: Option<Arc<Filter>>,
+}
+
+impl SearchQuery {
+    pub fn new(mode: SearchMode, target: SearchTarget, pattern: &str) -> Result<Self> {
+        let (pattern, target) = match mode {
+            SearchMode::Substring => (regex::escape(pattern), target),
+            SearchMode::Regex => (pattern.to_owned(), target),
+            SearchMode::Bytes => {
+                anyhow::ensure!(
+                    target == SearchTarget::Raw,
+                    "Byte patterns can only match raw bytes"
+                );
+                (parse_byte_pattern(pattern)?, target)
+            }
+        };
+
+        let regex = regex::bytes::RegexBuilder::new(&pattern)
+            .case_insensitive(target == SearchTarget::Hex)
+            .build()
+            .context("Invalid pattern")?;
+        Ok(Self {
+            regex,
+            target,
+            filter: None,
+        })
+    }
+
+    /// Only reports matches which also pass the filter.
+    pub fn set_filter(&mut self, filter: Option<Arc<Filter>>) {
+        self.filter = filter;
+    }
+
+    fn check(
+        &self,
+        key_repr: &dyn ValueRepr,
+        value_repr: &dyn ValueRepr,
+        key: &[u8],
+        value: &[u8],
+    ) -> Option<CfEntry> {
+        let is_match =
+            |key: &[u8], value: &[u8]| self.regex.is_match(key) || self.regex.is_match(value);
+
+        // Only matches are decoded when searching the raw data
+        let decode = || CfEntry {
+            key: key.into(),
+            value: value.into(),
+            decoded_key: key_repr.decode(key, key),
+            decoded_value: value_repr.decode(key, value),
+        };
+
+        let entry = match self.target {
+            SearchTarget::Raw => is_match(key, value).then(decode)?,
+            SearchTarget::Hex => {
+                is_match(hex::encode(key).as_bytes(), hex::encode(value).as_bytes()).then(decode)?
+            }
+            SearchTarget::Decoded => {
+                let entry = decode();
+                let found = is_match(
+                    entry.decoded_key.to_string().as_bytes(),
+                    entry.decoded_value.to_string().as_bytes(),
+                );
+                found.then_some(entry)?
+            }
+        };
+
+        match &self.filter {
+            Some(filter) if !filter.matches(&entry) => None,
+            _ => Some(entry),
+        }
+    }
+}
+
+/// Converts `de ad ?? ef` into a byte regex.
+fn parse_byte_pattern(pattern: &str) -> Result<String> {
+    let pattern = pattern.trim();
+    let digits = pattern
+        .strip_prefix("0x")
+        .unwrap_or(pattern)
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+    anyhow::ensure!(!digits.is_empty(), "Empty byte pattern");
+    anyhow::ensure!(digits.len() % 2 == 0, "Odd number of hex digits");
+
+    let mut result = "(?s-u)".to_owned();
+    for pair in digits.chunks(2) {
+        match pair {
+            ['?', '?'] => result.push('.'),
+            [hi, lo] if hi.is_ascii_hexdigit() && lo.is_ascii_hexdigit() => {
+                result += &format!("\\x{hi}{lo}");
+            }
+            _ => anyhow::bail!("Invalid byte: {}{}", pair[0], pair[1]),
+        }
+    }
+    Ok(result)
+}
+
+pub enum SearchEvent {
+    Match(Box<CfEntry>),
+    /// Position is the estimated fraction of the key space already scanned.
+    Progress {
+        scanned: usize,
+        position: f64,
+    },
+    Finished {
+        scanned: usize,
+        limit_reached: bool,
+    },
+    Failed(anyhow::Error),
+}
+
+pub(super) struct ScanParams {
+    pub cf_name: String,
+    pub key_repr: ReprKind,
+    pub value_repr: ReprKind,
+    pub query: Arc<SearchQuery>,
+    pub limit: usize,
+}
+
+/// Starts scanning one column family on a new thread, stopping when `task` is cancelled.
+pub(super) fn spawn_scan<F>(db: Arc<rocksdb::DB>, params: ScanParams, task: Task, mut on_event: F)
+where
+    F: FnMut(SearchEvent) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let event = match scan(&db, &params, &task, &mut on_event) {
+            Ok(event) => event,
+            Err(e) => SearchEvent::Failed(e),
+        };
+        on_event(event);
+    });
+}
+
+fn scan<F>(
+    db: &rocksdb::DB,
+    params: &ScanParams,
+    task: &Task,
+    on_event: &mut F,
+) -> Result<SearchEvent>
+where
+    F: FnMut(SearchEvent),
+{
+    let handle = db
+        .cf_handle(&params.cf_name)
+        .context("Column family not found")?;
+    let key_repr = params.key_repr.repr();
+    let value_repr = params.value_repr.repr();
+
+    let mut matches = 0;
+    let mut limit_reached = false;
+    let scanned = scan_cf(db, &handle, task, |key, value, position| {
+        if let Some(entry) = params.query.check(key_repr, value_repr, key, value) {
+            on_event(SearchEvent::Match(Box::new(entry)));
+            matches += 1;
+            if matches >= params.limit {
+                limit_reached = true;
+                return Ok(ControlFlow::Break(()));
+            }
+        }
+
+        if position.report_due {
+            on_event(SearchEvent::Progress {
+                scanned: position.scanned,
+                position: position.fraction,
+            });
+        }
+        Ok(ControlFlow::Continue(()))
+    })?;
+
+    Ok(SearchEvent::Finished {
+        scanned,
+        limit_reached,
+    })
+}
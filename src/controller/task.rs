@@ -0,0 +1,98 @@
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+/// Minimum time between two progress events of a background task.
+pub(super) const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Handle of a task running on a worker thread, used to cancel it.
+#[derive(Clone, Default)]
+pub struct Task {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Task {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+}
+
+/// Where [`scan_cf`] is in the column family.
+pub(super) struct ScanPosition {
+    /// Entries visited so far, including the current one.
+    pub scanned: usize,
+    /// Estimated fraction of the column family already visited.
+    pub fraction: f64,
+    /// Whether [`PROGRESS_INTERVAL`] has passed since the last due report.
+    pub report_due: bool,
+}
+
+/// Visits the entries of the column family in key order until `visit` breaks or
+/// `task` is cancelled, and returns the number of visited entries.
+pub(super) fn scan_cf<F>(
+    db: &rocksdb::DB,
+    handle: &impl rocksdb::AsColumnFamilyRef,
+    task: &Task,
+    mut visit: F,
+) -> Result<usize>
+where
+    F: FnMut(&[u8], &[u8], &ScanPosition) -> Result<ControlFlow<()>>,
+{
+    let last_key = match db.iterator_cf(handle, rocksdb::IteratorMode::End).next() {
+        Some(item) => item.context("Failed to read entry")?.0,
+        None => return Ok(0),
+    };
+
+    let mut scanned = 0;
+    let mut first_key = None;
+    let mut last_report = Instant::now();
+    for item in db.iterator_cf(handle, rocksdb::IteratorMode::Start) {
+        if task.is_cancelled() {
+            break;
+        }
+
+        let (key, value) = item.context("Failed to read entry")?;
+        let first_key = first_key.get_or_insert_with(|| key.clone());
+        scanned += 1;
+
+        let report_due = last_report.elapsed() >= PROGRESS_INTERVAL;
+        if report_due {
+            last_report = Instant::now();
+        }
+        let position = ScanPosition {
+            scanned,
+            fraction: key_position(first_key, &last_key, &key),
+            report_due,
+        };
+        if visit(&key, &value, &position)?.is_break() {
+            break;
+        }
+    }
+    Ok(scanned)
+}
+
+/// Linear estimate of where `key` lies between `first` and `last`,
+/// using the eight bytes after their common prefix.
+fn key_position(first: &[u8], last: &[u8], key: &[u8]) -> f64 {
+    let prefix = first.iter().zip(last).take_while(|(a, b)| a == b).count();
+    let read = |key: &[u8]| {
+        let tail = key.get(prefix..).unwrap_or_default();
+        let len = tail.len().min(8);
+        let mut bytes = [0; 8];
+        bytes[..len].copy_from_slice(&tail[..len]);
+        u64::from_be_bytes(bytes) as f64
+    };
+
+    let (first, last) = (read(first), read(last));
+    if last <= first {
+        return 0.0;
+    }
+    ((read(key) - first) / (last - first)).clamp(0.0, 1.0)
+}
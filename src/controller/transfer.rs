@@ -0,0 +1,165 @@
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+
+use super::task::{Task, PROGRESS_INTERVAL};
+
+const CHUNK_LEN: usize = 1 << 20;
+
+pub enum TransferEvent {
+    Progress {
+        done: usize,
+        total: usize,
+    },
+    /// Length of the value, or `None` if the transfer was cancelled.
+    Finished {
+        len: Option<usize>,
+    },
+    Failed(anyhow::Error),
+}
+
+/// Value copied between a column family and a file by [`spawn_save`] and [`spawn_replace`].
+pub(super) struct Transfer {
+    pub cf_name: String,
+    pub key: Vec<u8>,
+    pub path: PathBuf,
+}
+
+/// Writes the value to the file on a new thread, stopping when `task` is cancelled.
+pub(super) fn spawn_save<F>(db: Arc<rocksdb::DB>, transfer: Transfer, task: Task, mut on_event: F)
+where
+    F: FnMut(TransferEvent) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let event = match save(&db, &transfer, &task, &mut on_event) {
+            Ok(len) => TransferEvent::Finished { len },
+            Err(e) => TransferEvent::Failed(e),
+        };
+        on_event(event);
+    });
+}
+
+/// Replaces the value with the file contents on a new thread.
+///
+/// Nothing is written if `task` is cancelled before the whole file is read.
+pub(super) fn spawn_replace<F>(
+    db: Arc<rocksdb::DB>,
+    transfer: Transfer,
+    task: Task,
+    mut on_event: F,
+) where
+    F: FnMut(TransferEvent) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let event = match replace(&db, &transfer, &task, &mut on_event) {
+            Ok(len) => TransferEvent::Finished { len },
+            Err(e) => TransferEvent::Failed(e),
+        };
+        on_event(event);
+    });
+}
+
+fn save<F>(
+    db: &rocksdb::DB,
+    transfer: &Transfer,
+    task: &Task,
+    on_event: &mut F,
+) -> Result<Option<usize>>
+where
+    F: FnMut(TransferEvent),
+{
+    let handle = db
+        .cf_handle(&transfer.cf_name)
+        .context("Column family not found")?;
+    let value = db
+        .get_pinned_cf(&handle, &transfer.key)
+        .context("Failed to read value")?
+        .context("Value not found")?;
+
+    let file = File::create(&transfer.path).context("Failed to create file")?;
+    let mut writer = BufWriter::new(file);
+    let mut progress = Progress::new(value.len());
+    for chunk in value.chunks(CHUNK_LEN) {
+        if task.is_cancelled() {
+            // A partially written file is worse than none
+            drop(writer);
+            std::fs::remove_file(&transfer.path).ok();
+            return Ok(None);
+        }
+        writer.write_all(chunk).context("Failed to write file")?;
+        progress.advance(chunk.len(), on_event);
+    }
+    writer.flush().context("Failed to write file")?;
+    Ok(Some(value.len()))
+}
+
+fn replace<F>(
+    db: &rocksdb::DB,
+    transfer: &Transfer,
+    task: &Task,
+    on_event: &mut F,
+) -> Result<Option<usize>>
+where
+    F: FnMut(TransferEvent),
+{
+    let handle = db
+        .cf_handle(&transfer.cf_name)
+        .context("Column family not found")?;
+
+    let mut file = File::open(&transfer.path).context("Failed to open file")?;
+    let total = file.metadata().context("Failed to read file")?.len() as usize;
+
+    let mut value = Vec::with_capacity(total);
+    let mut progress = Progress::new(total);
+    let mut chunk = vec![0; CHUNK_LEN];
+    loop {
+        if task.is_cancelled() {
+            return Ok(None);
+        }
+        let len = file.read(&mut chunk).context("Failed to read file")?;
+        if len == 0 {
+            break;
+        }
+        value.extend_from_slice(&chunk[..len]);
+        progress.advance(len, on_event);
+    }
+
+    db.put_cf(&handle, &transfer.key, &value)
+        .context("Failed to write value")?;
+    Ok(Some(value.len()))
+}
+
+struct Progress {
+    done: usize,
+    total: usize,
+    last_report: Instant,
+}
+
+impl Progress {
+    fn new(total: usize) -> Self {
+        Self {
+            done: 0,
+            total,
+            last_report: Instant::now(),
+        }
+    }
+
+    fn advance<F>(&mut self, len: usize, on_event: &mut F)
+    where
+        F: FnMut(TransferEvent),
+    {
+        self.done += len;
+        if self.last_report.elapsed() >= PROGRESS_INTERVAL {
+            on_event(TransferEvent::Progress {
+                done: self.done,
+                // The file may grow while it is read
+                total: self.total.max(self.done),
+            });
+            self.last_report = Instant::now();
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Range;
+
+use super::models::{BlockIdFull, BlockIdShort, PackageEntryId};
+
+/// Decoded value together with the byte range it was read from.
+#[derive(Debug, Clone)]
+pub struct Value {
+    pub range: Range<usize>,
+    pub kind: ValueKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum ValueKind {
+    Uint(u64),
+    Int(i64),
+    Shard(u64),
+    Bytes(Vec<u8>),
+    Str(String),
+    Time(u64),
+    Blob(usize),
+    BlockIdShort(BlockIdShort),
+    BlockIdFull(BlockIdFull),
+    PackageEntryId(PackageEntryId),
+    /// Bit set with names by bit, empty names are reserved bits.
+    Flags {
+        bits: u64,
+        names: &'static [&'static str],
+    },
+    Struct(Vec<(&'static str, Value)>),
+    /// Items named by the data itself, e.g. archive entries.
+    List(Vec<(String, Value)>),
+    Error {
+        offset: usize,
+        reason: String,
+    },
+}
+
+impl Value {
+    pub fn new(range: Range<usize>, kind: ValueKind) -> Self {
+        Self { range, kind }
+    }
+
+    pub fn error(range: Range<usize>, offset: usize, reason: impl Into<String>) -> Self {
+        Self::new(
+            range,
+            ValueKind::Error {
+                offset,
+                reason: reason.into(),
+            },
+        )
+    }
+
+    /// Error for values which must have exactly `expected` bytes.
+    pub fn invalid_len(data: &[u8], expected: usize) -> Self {
+        Self::error(
+            0..data.len(),
+            data.len().min(expected),
+            format!("expected {expected} bytes, got {}", data.len()),
+        )
+    }
+
+    /// Numeric value of integer kinds.
+    pub fn as_number(&self) -> Option<i128> {
+        match self.kind {
+            ValueKind::Uint(value) | ValueKind::Shard(value) | ValueKind::Time(value) => {
+                Some(value as i128)
+            }
+            ValueKind::Int(value) => Some(value as i128),
+            ValueKind::Flags { bits, .. } => Some(bits as i128),
+            _ => None,
+        }
+    }
+
+    /// Full block id the value links to.
+    pub fn block_id(&self) -> Option<BlockIdFull> {
+        match &self.kind {
+            ValueKind::BlockIdFull(id) => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// Block id in the form accepted by [`super::Db::explore_block`], if the value is one.
+    pub fn block_id_text(&self) -> Option<String> {
+        match &self.kind {
+            ValueKind::BlockIdShort(id) => Some(id.to_string()),
+            ValueKind::BlockIdFull(id) => Some(id.to_string()),
+            ValueKind::PackageEntryId(id) => Some(id.block_id.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Orders numbers numerically and composite values field by field,
+    /// falling back to the displayed text.
+    pub fn compare(&self, other: &Self) -> Ordering {
+        if let (Some(left), Some(right)) = (self.as_number(), other.as_number()) {
+            return left.cmp(&right);
+        }
+        match (&self.kind, &other.kind) {
+            (ValueKind::Bytes(left), ValueKind::Bytes(right)) => return left.cmp(right),
+            (ValueKind::Blob(left), ValueKind::Blob(right)) => return left.cmp(right),
+            _ => {}
+        }
+
+        let (left, right) = (self.children(), other.children());
+        if left.is_empty() || right.is_empty() {
+            return self.to_string().cmp(&other.to_string());
+        }
+        left.iter()
+            .zip(&right)
+            .map(|((_, left), (_, right))| left.compare(right))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| left.len().cmp(&right.len()))
+    }
+
+    /// Dot-separated paths of all nested fields.
+    pub fn field_paths(&self) -> Vec<String> {
+        // List items differ between rows
+        if let ValueKind::List(_) = self.kind {
+            return Vec::new();
+        }
+
+        let mut paths = Vec::new();
+        for (name, child) in self.children() {
+            let nested = child.field_paths();
+            paths.push(name.clone());
+            paths.extend(nested.into_iter().map(|path| format!("{name}.{path}")));
+        }
+        paths
+    }
+
+    /// Nested field by its dot-separated path.
+    pub fn field(&self, path: &str) -> Option<Value> {
+        path.split('.')
+            .try_fold(self.clone(), |value, name| value.child(name))
+    }
+
+    pub fn child(&self, name: &str) -> Option<Value> {
+        self.children()
+            .into_iter()
+            .find_map(|(child_name, value)| (child_name == name).then_some(value))
+    }
+
+    /// Named parts of the value, with absolute byte ranges.
+    pub fn children(&self) -> Vec<(String, Value)> {
+        let start = self.range.start;
+        let field = |name: &str, range: Range<usize>, kind: ValueKind| {
+            let range = start + range.start..start + range.end;
+            (name.to_owned(), Value::new(range, kind))
+        };
+
+        match &self.kind {
+            ValueKind::BlockIdShort(id) => block_id_children(id, start),
+            ValueKind::BlockIdFull(id) => {
+                let mut children = block_id_children(&id.short(), start);
+                children.push(field(
+                    "root_hash",
+                    16..48,
+                    ValueKind::Bytes(id.root_hash.to_vec()),
+                ));
+                children.push(field(
+                    "file_hash",
+                    48..80,
+                    ValueKind::Bytes(id.file_hash.to_vec()),
+                ));
+                children
+            }
+            ValueKind::PackageEntryId(id) => {
+                let mut children = block_id_children(&id.block_id, start);
+                children.push(field(
+                    "root_hash",
+                    16..48,
+                    ValueKind::Bytes(id.root_hash.to_vec()),
+                ));
+                children.push(field(
+                    "package_type",
+                    48..49,
+                    ValueKind::Str(id.package_type.to_string()),
+                ));
+                children
+            }
+            // Each flag is 0 or 1, e.g. for `value.flags.applied == 0`
+            ValueKind::Flags { bits, names } => names
+                .iter()
+                .enumerate()
+                .filter(|(_, name)| !name.is_empty())
+                .map(|(bit, name)| {
+                    let kind = ValueKind::Uint(bits >> bit & 1);
+                    ((*name).to_owned(), Value::new(self.range.clone(), kind))
+                })
+                .collect(),
+            ValueKind::Struct(fields) => fields
+                .iter()
+                .map(|(name, value)| ((*name).to_owned(), value.clone()))
+                .collect(),
+            ValueKind::List(items) => items.clone(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn block_id_children(id: &BlockIdShort, start: usize) -> Vec<(String, Value)> {
+    vec![
+        (
+            "workchain".to_owned(),
+            Value::new(start..start + 4, ValueKind::Int(id.workchain as i64)),
+        ),
+        (
+            "shard".to_owned(),
+            Value::new(start + 4..start + 12, ValueKind::Shard(id.shard)),
+        ),
+        (
+            "seqno".to_owned(),
+            Value::new(start + 12..start + 16, ValueKind::Uint(id.seqno as u64)),
+        ),
+    ]
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ValueKind::Uint(value) => value.fmt(f),
+            ValueKind::Int(value) => value.fmt(f),
+            ValueKind::Shard(shard) => write!(f, "{shard:016x}"),
+            ValueKind::Bytes(bytes) => f.write_str(&hex::encode(bytes)),
+            ValueKind::Str(value) => f.write_str(value),
+            ValueKind::Time(secs) => f.write_str(&format_unix_time(*secs)),
+            ValueKind::Blob(len) => write!(f, "<{len} bytes>"),
+            ValueKind::BlockIdShort(id) => id.fmt(f),
+            ValueKind::BlockIdFull(id) => id.fmt(f),
+            ValueKind::PackageEntryId(id) => id.fmt(f),
+            ValueKind::Flags { bits, names } => {
+                let mut unknown = *bits;
+                let mut first = true;
+                for (bit, name) in names.iter().enumerate() {
+                    if name.is_empty() || bits & (1 << bit) == 0 {
+                        continue;
+                    }
+                    unknown &= !(1 << bit);
+                    f.write_str(if first { "" } else { " | " })?;
+                    f.write_str(name)?;
+                    first = false;
+                }
+                match (unknown, first) {
+                    (0, true) => f.write_str("none"),
+                    (0, false) => Ok(()),
+                    (unknown, true) => write!(f, "{unknown:#x}"),
+                    (unknown, false) => write!(f, " | {unknown:#x}"),
+                }
+            }
+            ValueKind::Struct(fields) => {
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{name}: {value}")?;
+                }
+                Ok(())
+            }
+            ValueKind::List(items) => write!(f, "[{} items]", items.len()),
+            ValueKind::Error { reason, .. } => write!(f, "<invalid: {reason}>"),
+        }
+    }
+}
+
+pub(super) fn format_unix_time(secs: u64) -> String {
+    // Civil date from days since epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (secs / 86400) as i64 + 719468;
+    let time = secs % 86400;
+
+    let era = days.div_euclid(146097);
+    let doe = days.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unix_time() {
+        assert_eq!(format_unix_time(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_unix_time(951782400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(format_unix_time(1700000000), "2023-11-14 22:13:20 UTC");
+        assert_eq!(format_unix_time(7258118399), "2199-12-31 23:59:59 UTC");
+    }
+}
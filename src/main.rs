@@ -1,5 +1,6 @@
 mod app;
 mod controller;
+mod settings;
 mod ui;
 mod window;
 
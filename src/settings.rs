@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use gtk::glib;
+
+use crate::controller::ReprKind;
+
+/// Decoders picked by the user, remembered per DB path and column family.
+#[derive(Default)]
+pub struct DecoderSettings {
+    entries: HashMap<(PathBuf, String), (ReprKind, ReprKind)>,
+}
+
+impl DecoderSettings {
+    pub fn load() -> Self {
+        let Ok(data) = std::fs::read_to_string(Self::file_path()) else {
+            return Self::default();
+        };
+
+        let mut entries = HashMap::new();
+        for line in data.lines() {
+            let mut parts = line.split('\t');
+            let (Some(path), Some(cf_name), Some(key_repr), Some(value_repr)) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+
+            let (Some(key_repr), Some(value_repr)) =
+                (ReprKind::from_id(key_repr), ReprKind::from_id(value_repr))
+            else {
+                continue;
+            };
+
+            entries.insert(
+                (PathBuf::from(path), cf_name.to_owned()),
+                (key_repr, value_repr),
+            );
+        }
+
+        Self { entries }
+    }
+
+    pub fn get(&self, db_path: &Path, cf_name: &str) -> Option<(ReprKind, ReprKind)> {
+        self.entries
+            .get(&(db_path.to_owned(), cf_name.to_owned()))
+            .copied()
+    }
+
+    pub fn set(
+        &mut self,
+        db_path: &Path,
+        cf_name: &str,
+        key_repr: ReprKind,
+        value_repr: ReprKind,
+    ) -> Result<()> {
+        self.entries.insert(
+            (db_path.to_owned(), cf_name.to_owned()),
+            (key_repr, value_repr),
+        );
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        let mut data = String::new();
+        for ((path, cf_name), (key_repr, value_repr)) in &self.entries {
+            data += &format!(
+                "{}\t{cf_name}\t{}\t{}\n",
+                path.display(),
+                key_repr.id(),
+                value_repr.id()
+            );
+        }
+
+        let path = Self::file_path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).context("Failed to create settings directory")?;
+        }
+        std::fs::write(path, data).context("Failed to save decoder settings")
+    }
+
+    fn file_path() -> PathBuf {
+        glib::user_config_dir()
+            .join("rocksdb-viewer")
+            .join("decoders.tsv")
+    }
+}
@@ -0,0 +1,191 @@
+use gtk::glib;
+use gtk::prelude::*;
+
+use crate::controller::{ArchiveCoverage, Continuity};
+
+/// Page listing the masterchain range of every archive, with gaps and overlaps highlighted.
+pub struct ArchiveCoverageView {
+    container: gtk::Box,
+    scan_btn: gtk::Button,
+    cancel_btn: gtk::Button,
+    progress_bar: gtk::ProgressBar,
+    tree_view: gtk::TreeView,
+    store: gtk::ListStore,
+}
+
+impl ArchiveCoverageView {
+    pub fn new() -> Self {
+        let scan_btn = gtk::Button::with_label("Scan archives");
+        let cancel_btn = gtk::Button::with_label("Cancel");
+        cancel_btn.set_sensitive(false);
+
+        let progress_bar = gtk::ProgressBar::new();
+        progress_bar.set_show_text(true);
+        progress_bar.set_valign(gtk::Align::Center);
+
+        let toolbar = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        toolbar.set_border_width(4);
+        toolbar.pack_start(&scan_btn, false, false, 0);
+        toolbar.pack_start(&progress_bar, true, true, 0);
+        toolbar.pack_start(&cancel_btn, false, false, 0);
+
+        let column_types = [
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Bytes::static_type(),
+        ];
+
+        let store = gtk::ListStore::new(&column_types);
+        let tree_view = gtk::TreeView::with_model(&store);
+        add_text_column(&tree_view, Column::ArchiveId, "Archive id");
+        add_text_column(&tree_view, Column::McSeqnos, "MC seqnos");
+        add_text_column(&tree_view, Column::Entries, "Entries");
+        add_text_column(&tree_view, Column::Size, "Size");
+        add_text_column(&tree_view, Column::Status, "Status");
+
+        let scrolled_window =
+            gtk::ScrolledWindow::new(gtk::Adjustment::NONE, gtk::Adjustment::NONE);
+        scrolled_window.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
+        scrolled_window.add(&tree_view);
+
+        let container = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        container.pack_start(&toolbar, false, false, 0);
+        container.pack_start(&scrolled_window, true, true, 0);
+
+        Self {
+            container,
+            scan_btn,
+            cancel_btn,
+            progress_bar,
+            tree_view,
+            store,
+        }
+    }
+
+    pub fn connect_scan<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.scan_btn.connect_clicked(move |_| f());
+    }
+
+    pub fn connect_cancel<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.cancel_btn.connect_clicked(move |_| f());
+    }
+
+    /// Calls `f` with the raw key of an activated archive.
+    pub fn connect_archive_activated<F>(&self, f: F)
+    where
+        F: Fn(&[u8]) + 'static,
+    {
+        let store = self.store.clone();
+        self.tree_view.connect_row_activated(move |_, path, _| {
+            let Some(iter) = store.iter(path) else {
+                return;
+            };
+
+            let key = store.value(&iter, Column::RawKey as i32);
+            f(&key.get::<glib::Bytes>().unwrap());
+        });
+    }
+
+    pub fn clear(&self) {
+        self.store.clear();
+        self.progress_bar.set_fraction(0.0);
+        self.progress_bar.set_text(Some(""));
+    }
+
+    pub fn append(&self, archive: ArchiveCoverage) {
+        let mc_seqnos = match &archive.mc_seqnos {
+            Some(range) => format!("{}–{}", range.start(), range.end()),
+            None => String::new(),
+        };
+        let (mut status, background) = match &archive.continuity {
+            Continuity::First | Continuity::Continuous => (String::new(), None),
+            Continuity::Gap(range) => (
+                format!("gap: {}–{} in no archive", range.start(), range.end()),
+                Some("#f4c7c3"),
+            ),
+            Continuity::Overlap(range) => (
+                format!(
+                    "overlap: {}–{} also in a previous archive",
+                    range.start(),
+                    range.end()
+                ),
+                Some("#fce8b2"),
+            ),
+            Continuity::Unknown => ("no masterchain entries".to_owned(), Some("#e0e0e0")),
+        };
+        if let Some(error) = &archive.error {
+            if !status.is_empty() {
+                status.push_str(", ");
+            }
+            status.push_str(error);
+        }
+
+        self.store.insert_with_values(
+            None,
+            &[
+                (Column::ArchiveId as u32, &archive.archive_id.to_string()),
+                (Column::McSeqnos as u32, &mc_seqnos),
+                (Column::Entries as u32, &archive.entries.to_string()),
+                (Column::Size as u32, &archive.size.to_string()),
+                (Column::Status as u32, &status),
+                (Column::Background as u32, &background),
+                (Column::RawKey as u32, &glib::Bytes::from_owned(archive.key)),
+            ],
+        );
+    }
+
+    pub fn set_running(&self, running: bool) {
+        self.scan_btn.set_sensitive(!running);
+        self.cancel_btn.set_sensitive(running);
+    }
+
+    pub fn set_progress(&self, fraction: f64, text: &str) {
+        self.progress_bar.set_fraction(fraction);
+        self.progress_bar.set_text(Some(text));
+    }
+}
+
+impl AsRef<gtk::Box> for ArchiveCoverageView {
+    fn as_ref(&self) -> &gtk::Box {
+        &self.container
+    }
+}
+
+fn add_text_column(tree_view: &gtk::TreeView, column: Column, title: &str) {
+    let renderer = gtk::CellRendererText::new();
+    let view_column = gtk::TreeViewColumn::new();
+    view_column.set_resizable(true);
+    view_column.set_title(title);
+    TreeViewColumnExt::pack_start(&view_column, &renderer, true);
+    TreeViewColumnExt::add_attribute(&view_column, &renderer, "text", column as i32);
+    // Gaps and overlaps are highlighted
+    TreeViewColumnExt::add_attribute(
+        &view_column,
+        &renderer,
+        "cell-background",
+        Column::Background as i32,
+    );
+    tree_view.append_column(&view_column);
+}
+
+#[derive(Debug)]
+#[repr(i32)]
+enum Column {
+    ArchiveId,
+    McSeqnos,
+    Entries,
+    Size,
+    Status,
+    Background,
+    RawKey,
+}
@@ -0,0 +1,194 @@
+use std::rc::Rc;
+
+use gtk::glib;
+use gtk::prelude::*;
+
+use crate::controller::{BlockIdFull, BlockItem};
+
+/// Page listing the rows of every column family which belong to one block.
+pub struct BlockExplorer {
+    container: gtk::Box,
+    entry: gtk::Entry,
+    show_btn: gtk::Button,
+    graph_btn: gtk::Button,
+    id_label: gtk::Label,
+    tree_view: gtk::TreeView,
+    store: gtk::ListStore,
+}
+
+impl BlockExplorer {
+    pub fn new() -> Self {
+        let entry = gtk::Entry::new();
+        entry.set_placeholder_text(Some("workchain:shard:seqno[:root_hash:file_hash]"));
+        let show_btn = gtk::Button::with_label("Show");
+        let graph_btn = gtk::Button::with_label("Chain graph");
+        graph_btn.set_tooltip_text(Some("Show the blocks linked to this one"));
+
+        let input_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        input_box.set_border_width(4);
+        input_box.pack_start(&gtk::Label::new(Some("Block:")), false, false, 0);
+        input_box.pack_start(&entry, true, true, 0);
+        input_box.pack_start(&show_btn, false, false, 0);
+        input_box.pack_start(&graph_btn, false, false, 0);
+
+        let id_label = gtk::Label::new(None);
+        id_label.set_selectable(true);
+        id_label.set_xalign(0.0);
+        id_label.set_margin_start(4);
+        id_label.set_ellipsize(gtk::pango::EllipsizeMode::Middle);
+
+        let column_types = [
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Bytes::static_type(),
+        ];
+
+        let store = gtk::ListStore::new(&column_types);
+        let tree_view = gtk::TreeView::with_model(&store);
+        add_text_column(&tree_view, Column::Name, "Item");
+        add_text_column(&tree_view, Column::CfName, "Column family");
+        add_text_column(&tree_view, Column::Size, "Size");
+        add_text_column(&tree_view, Column::Summary, "Value");
+
+        let scrolled_window =
+            gtk::ScrolledWindow::new(gtk::Adjustment::NONE, gtk::Adjustment::NONE);
+        scrolled_window.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
+        scrolled_window.add(&tree_view);
+
+        let container = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        container.pack_start(&input_box, false, false, 0);
+        container.pack_start(&id_label, false, false, 0);
+        container.pack_start(&scrolled_window, true, true, 0);
+
+        Self {
+            container,
+            entry,
+            show_btn,
+            graph_btn,
+            id_label,
+            tree_view,
+            store,
+        }
+    }
+
+    /// Calls `f` with the entered block id.
+    pub fn connect_explore<F>(&self, f: F)
+    where
+        F: Fn(&str) + 'static,
+    {
+        let f = Rc::new(f);
+        self.entry.connect_activate({
+            let f = f.clone();
+            move |entry| f(entry.text().trim())
+        });
+        self.show_btn.connect_clicked({
+            let entry = self.entry.clone();
+            move |_| f(entry.text().trim())
+        });
+    }
+
+    /// Calls `f` with the id of the shown block when its chain graph is requested.
+    pub fn connect_show_graph<F>(&self, f: F)
+    where
+        F: Fn(&str) + 'static,
+    {
+        let id_label = self.id_label.clone();
+        self.graph_btn.connect_clicked(move |_| {
+            let text = id_label.text();
+            if !text.is_empty() {
+                f(&text);
+            }
+        });
+    }
+
+    /// Calls `f` with the column family and key of an activated item.
+    pub fn connect_item_activated<F>(&self, f: F)
+    where
+        F: Fn(&str, &[u8]) + 'static,
+    {
+        let store = self.store.clone();
+        self.tree_view.connect_row_activated(move |_, path, _| {
+            let Some(iter) = store.iter(path) else {
+                return;
+            };
+
+            let cf_name = store.value(&iter, Column::CfName as i32);
+            let key = store.value(&iter, Column::RawKey as i32);
+            f(
+                cf_name.get::<&str>().unwrap(),
+                &key.get::<glib::Bytes>().unwrap(),
+            );
+        });
+    }
+
+    pub fn set_block_text(&self, text: &str) {
+        self.entry.set_text(text);
+    }
+
+    pub fn show(&self, id: &BlockIdFull, items: Vec<BlockItem>) {
+        self.id_label.set_text(&id.to_string());
+        self.store.clear();
+        for item in items {
+            self.append(item);
+        }
+    }
+
+    /// Adds a row found after the others, e.g. the archive.
+    pub fn append(&self, item: BlockItem) {
+        let size = item.size.map(|size| size.to_string()).unwrap_or_default();
+        let foreground = (!item.found).then_some("gray");
+        self.store.insert_with_values(
+            None,
+            &[
+                (Column::Name as u32, &item.name),
+                (Column::CfName as u32, &item.cf_name),
+                (Column::Size as u32, &size),
+                (Column::Summary as u32, &item.summary),
+                (Column::Foreground as u32, &foreground),
+                (Column::RawKey as u32, &glib::Bytes::from_owned(item.key)),
+            ],
+        );
+    }
+
+    pub fn clear(&self) {
+        self.id_label.set_text("");
+        self.store.clear();
+    }
+}
+
+impl AsRef<gtk::Box> for BlockExplorer {
+    fn as_ref(&self) -> &gtk::Box {
+        &self.container
+    }
+}
+
+fn add_text_column(tree_view: &gtk::TreeView, column: Column, title: &str) {
+    let renderer = gtk::CellRendererText::new();
+    let view_column = gtk::TreeViewColumn::new();
+    view_column.set_resizable(true);
+    view_column.set_title(title);
+    TreeViewColumnExt::pack_start(&view_column, &renderer, true);
+    TreeViewColumnExt::add_attribute(&view_column, &renderer, "text", column as i32);
+    // Missing rows are grayed out
+    TreeViewColumnExt::add_attribute(
+        &view_column,
+        &renderer,
+        "foreground",
+        Column::Foreground as i32,
+    );
+    tree_view.append_column(&view_column);
+}
+
+#[derive(Debug)]
+#[repr(i32)]
+enum Column {
+    Name,
+    CfName,
+    Size,
+    Summary,
+    Foreground,
+    RawKey,
+}
@@ -0,0 +1,108 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::prelude::*;
+
+/// Shard and seqno range picker for column families keyed by block id.
+pub struct BlockNavigator {
+    container: gtk::Box,
+    shard_combo: gtk::ComboBoxText,
+    from_entry: gtk::Entry,
+    to_entry: gtk::Entry,
+    go_btn: gtk::Button,
+    shards: Rc<RefCell<Vec<(i32, u64)>>>,
+}
+
+impl BlockNavigator {
+    pub fn new() -> Self {
+        let shard_combo = gtk::ComboBoxText::new();
+
+        let from_entry = gtk::Entry::new();
+        from_entry.set_placeholder_text(Some("first"));
+        from_entry.set_width_chars(12);
+
+        let to_entry = gtk::Entry::new();
+        to_entry.set_placeholder_text(Some("last"));
+        to_entry.set_width_chars(12);
+
+        let go_btn = gtk::Button::with_label("Show");
+
+        let container = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        container.set_border_width(4);
+        container.pack_start(&gtk::Label::new(Some("Shard:")), false, false, 0);
+        container.pack_start(&shard_combo, false, false, 0);
+        container.pack_start(&gtk::Label::new(Some("Seqno from")), false, false, 0);
+        container.pack_start(&from_entry, false, false, 0);
+        container.pack_start(&gtk::Label::new(Some("to")), false, false, 0);
+        container.pack_start(&to_entry, false, false, 0);
+        container.pack_start(&go_btn, false, false, 0);
+
+        // Hidden until shards are set
+        container.show_all();
+        container.hide();
+        container.set_no_show_all(true);
+
+        Self {
+            container,
+            shard_combo,
+            from_entry,
+            to_entry,
+            go_btn,
+            shards: Default::default(),
+        }
+    }
+
+    /// Shows the navigator for the discovered shards, or hides it if `shards` is `None`.
+    pub fn set_shards(&self, shards: Option<Vec<(i32, u64)>>) {
+        self.shard_combo.remove_all();
+        let Some(shards) = shards else {
+            self.shards.borrow_mut().clear();
+            self.container.hide();
+            return;
+        };
+
+        for (workchain, shard) in &shards {
+            self.shard_combo
+                .append_text(&format!("{workchain}:{shard:016x}"));
+        }
+        self.shard_combo.set_active(Some(0));
+        *self.shards.borrow_mut() = shards;
+
+        self.container.show();
+    }
+
+    /// Calls `f` with the selected workchain, shard and the entered seqno bounds.
+    pub fn connect_navigate<F>(&self, f: F)
+    where
+        F: Fn(i32, u64, &str, &str) + 'static,
+    {
+        let emit = {
+            let shard_combo = self.shard_combo.clone();
+            let from_entry = self.from_entry.clone();
+            let to_entry = self.to_entry.clone();
+            let shards = self.shards.clone();
+            move || {
+                let shards = shards.borrow();
+                let Some(&(workchain, shard)) =
+                    shard_combo.active().and_then(|i| shards.get(i as usize))
+                else {
+                    return;
+                };
+                f(workchain, shard, &from_entry.text(), &to_entry.text());
+            }
+        };
+
+        let emit = Rc::new(emit);
+        for entry in [&self.from_entry, &self.to_entry] {
+            let emit = emit.clone();
+            entry.connect_activate(move |_| emit());
+        }
+        self.go_btn.connect_clicked(move |_| emit());
+    }
+}
+
+impl AsRef<gtk::Box> for BlockNavigator {
+    fn as_ref(&self) -> &gtk::Box {
+        &self.container
+    }
+}
@@ -0,0 +1,197 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::glib;
+use gtk::prelude::*;
+
+use crate::controller::{CellInfo, CellSource};
+
+/// Cell tree browser which loads the references of a cell when it is expanded.
+pub struct CellTree {
+    container: gtk::ScrolledWindow,
+    tree_view: gtk::TreeView,
+    store: gtk::TreeStore,
+    source: Rc<RefCell<Option<Rc<dyn CellSource>>>>,
+}
+
+impl CellTree {
+    pub fn new() -> Self {
+        let column_types = [
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::U64,
+            glib::Type::U32,
+            glib::Type::U32,
+            glib::Type::U32,
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::BOOL,
+        ];
+
+        let store = gtk::TreeStore::new(&column_types);
+        let tree_view = gtk::TreeView::with_model(&store);
+        add_text_column(&tree_view, Column::Name, "Cell");
+        add_text_column(&tree_view, Column::Type, "Type");
+        add_text_column(&tree_view, Column::Bits, "Bits");
+        add_text_column(&tree_view, Column::Refs, "Refs");
+        add_text_column(&tree_view, Column::LevelMask, "Level mask");
+        add_text_column(&tree_view, Column::Depth, "Depth");
+        add_text_column(&tree_view, Column::Hash, "Hash");
+        add_text_column(&tree_view, Column::Data, "Data");
+
+        let source = Rc::new(RefCell::new(None::<Rc<dyn CellSource>>));
+
+        tree_view.connect_test_expand_row({
+            let store = store.clone();
+            let source = source.clone();
+            move |_, iter, _| {
+                if !store
+                    .value(iter, Column::Loaded as i32)
+                    .get::<bool>()
+                    .unwrap()
+                {
+                    if let Some(source) = &*source.borrow() {
+                        load_children(&store, iter, source.as_ref());
+                    }
+                }
+                Inhibit(false)
+            }
+        });
+
+        let container = gtk::ScrolledWindow::new(gtk::Adjustment::NONE, gtk::Adjustment::NONE);
+        container.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
+        container.add(&tree_view);
+
+        Self {
+            container,
+            tree_view,
+            store,
+            source,
+        }
+    }
+
+    /// Shows the root cells, their children are loaded from `source` on demand.
+    pub fn show(&self, source: Rc<dyn CellSource>, roots: &[[u8; 32]]) {
+        self.clear();
+        for (i, hash) in roots.iter().enumerate() {
+            let name = format!("root {i}");
+            match source.load_cell(hash) {
+                Ok(cell) => insert_cell(&self.store, None, &name, &cell),
+                Err(e) => insert_error(&self.store, None, &name, &e),
+            }
+        }
+        *self.source.borrow_mut() = Some(source);
+
+        if roots.len() == 1 {
+            self.tree_view
+                .expand_row(&gtk::TreePath::new_first(), false);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.store.clear();
+        self.source.borrow_mut().take();
+    }
+}
+
+impl AsRef<gtk::ScrolledWindow> for CellTree {
+    fn as_ref(&self) -> &gtk::ScrolledWindow {
+        &self.container
+    }
+}
+
+fn load_children(store: &gtk::TreeStore, parent: &gtk::TreeIter, source: &dyn CellSource) {
+    // Remove the placeholder row
+    while let Some(child) = store.iter_children(Some(parent)) {
+        store.remove(&child);
+    }
+    store.set_value(parent, Column::Loaded as u32, &true.to_value());
+
+    let hash = store.value(parent, Column::Hash as i32);
+    let mut parent_hash = [0; 32];
+    match hash.get::<&str>() {
+        Ok(hash) if hex::decode_to_slice(hash, &mut parent_hash).is_ok() => {}
+        _ => return,
+    }
+
+    let refs = match source.load_cell(&parent_hash) {
+        Ok(cell) => cell.refs,
+        Err(e) => return insert_error(store, Some(parent), "error", &e),
+    };
+    for (i, hash) in refs.iter().enumerate() {
+        let name = format!("ref {i}");
+        match source.load_cell(hash) {
+            Ok(cell) => insert_cell(store, Some(parent), &name, &cell),
+            Err(e) => insert_error(store, Some(parent), &name, &e),
+        }
+    }
+}
+
+fn insert_cell(
+    store: &gtk::TreeStore,
+    parent: Option<&gtk::TreeIter>,
+    name: &str,
+    cell: &CellInfo,
+) {
+    let iter = store.insert_with_values(
+        parent,
+        None,
+        &[
+            (Column::Name as u32, &name),
+            (Column::Type as u32, &cell.cell_type.name()),
+            (Column::Bits as u32, &(cell.bit_len as u64)),
+            (Column::Refs as u32, &(cell.refs.len() as u32)),
+            (Column::LevelMask as u32, &(cell.level_mask as u32)),
+            (Column::Depth as u32, &(cell.depth as u32)),
+            (Column::Hash as u32, &hex::encode(cell.hash)),
+            (Column::Data as u32, &cell.data_hex()),
+            (Column::Loaded as u32, &cell.refs.is_empty()),
+        ],
+    );
+
+    // Makes the row expandable until the references are loaded
+    if !cell.refs.is_empty() {
+        store.insert_with_values(Some(&iter), None, &[(Column::Loaded as u32, &true)]);
+    }
+}
+
+fn insert_error(
+    store: &gtk::TreeStore,
+    parent: Option<&gtk::TreeIter>,
+    name: &str,
+    error: &anyhow::Error,
+) {
+    store.insert_with_values(
+        parent,
+        None,
+        &[
+            (Column::Name as u32, &name),
+            (Column::Type as u32, &format!("<{error}>")),
+            (Column::Loaded as u32, &true),
+        ],
+    );
+}
+
+fn add_text_column(tree_view: &gtk::TreeView, column: Column, title: &str) {
+    let renderer = gtk::CellRendererText::new();
+    let view_column = gtk::TreeViewColumn::new();
+    view_column.set_resizable(true);
+    view_column.set_title(title);
+    TreeViewColumnExt::pack_start(&view_column, &renderer, true);
+    TreeViewColumnExt::add_attribute(&view_column, &renderer, "text", column as i32);
+    tree_view.append_column(&view_column);
+}
+
+#[derive(Debug)]
+#[repr(i32)]
+enum Column {
+    Name,
+    Type,
+    Bits,
+    Refs,
+    LevelMask,
+    Depth,
+    Hash,
+    Data,
+    Loaded,
+}
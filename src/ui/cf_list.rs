@@ -35,6 +35,18 @@ impl CfList {
         }
     }
 
+    /// Highlights the column family without activating it.
+    pub fn select_cf(&self, cf_name: &str) {
+        let selection = self.tree_view.selection();
+        self.store.foreach(|model, _, iter| {
+            let found = model.value(iter, Column::Name as i32).get::<&str>() == Ok(cf_name);
+            if found {
+                selection.select_iter(iter);
+            }
+            found
+        });
+    }
+
     pub fn connect_cf_selected<F>(&self, f: F)
     where
         F: Fn(&str) + 'static,
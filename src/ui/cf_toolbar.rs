@@ -0,0 +1,81 @@
+use gtk::prelude::*;
+
+use crate::controller::LinkTarget;
+
+pub struct CfToolbar {
+    container: gtk::Box,
+    pub back_btn: gtk::Button,
+    pub forward_btn: gtk::Button,
+    pub seek_entry: gtk::Entry,
+    pub get_btn: gtk::Button,
+    pub columns_btn: gtk::MenuButton,
+    link_target: gtk::ComboBoxText,
+}
+
+impl CfToolbar {
+    pub fn new() -> Self {
+        let back_btn = gtk::Button::from_icon_name(Some("go-previous"), gtk::IconSize::Button);
+        back_btn.set_tooltip_text(Some("Back"));
+        back_btn.set_sensitive(false);
+
+        let forward_btn = gtk::Button::from_icon_name(Some("go-next"), gtk::IconSize::Button);
+        forward_btn.set_tooltip_text(Some("Forward"));
+        forward_btn.set_sensitive(false);
+
+        let seek_entry = gtk::Entry::new();
+        seek_entry.set_placeholder_text(Some("Key as displayed, or 0x-prefixed hex"));
+
+        let get_btn = gtk::Button::with_label("Get…");
+        get_btn.set_tooltip_text(Some("Look up one or many keys"));
+
+        let columns_btn = gtk::MenuButton::new();
+        columns_btn.set_label("Columns");
+
+        let link_target = gtk::ComboBoxText::new();
+        link_target.set_tooltip_text(Some("Column family opened by clicking a block id"));
+        for target in LinkTarget::ALL {
+            link_target.append_text(&target.name());
+        }
+        link_target.set_active(Some(0));
+
+        let container = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        container.set_border_width(4);
+        container.pack_start(&back_btn, false, false, 0);
+        container.pack_start(&forward_btn, false, false, 0);
+        container.pack_start(&gtk::Label::new(Some("Seek:")), false, false, 0);
+        container.pack_start(&seek_entry, true, true, 0);
+        container.pack_start(&get_btn, false, false, 0);
+        container.pack_start(&columns_btn, false, false, 0);
+        container.pack_start(&gtk::Label::new(Some("Links to:")), false, false, 0);
+        container.pack_start(&link_target, false, false, 0);
+
+        Self {
+            container,
+            back_btn,
+            forward_btn,
+            seek_entry,
+            get_btn,
+            columns_btn,
+            link_target,
+        }
+    }
+
+    pub fn link_target(&self) -> LinkTarget {
+        let index = self.link_target.active().unwrap_or_default() as usize;
+        LinkTarget::ALL[index]
+    }
+
+    pub fn connect_seek<F>(&self, f: F)
+    where
+        F: Fn(&str) + 'static,
+    {
+        self.seek_entry
+            .connect_activate(move |entry| f(entry.text().as_str()));
+    }
+}
+
+impl AsRef<gtk::Box> for CfToolbar {
+    fn as_ref(&self) -> &gtk::Box {
+        &self.container
+    }
+}
@@ -1,36 +1,412 @@
-use gtk::glib;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::rc::Rc;
+
 use gtk::prelude::*;
+use gtk::{gdk, glib};
+
+use super::{tsv_field, CopyContent, CopyFormat};
+use crate::controller::{BlockIdFull, CfEntry, ReprKind, Value};
 
 pub struct CfView {
     pub tree_view: gtk::TreeView,
     pub store: gtk::ListStore,
+    pub columns_menu: gtk::Menu,
+    rows: Rc<RefCell<Vec<DecodedRow>>>,
+    copy_items: Vec<(CopyContent, gtk::MenuItem)>,
+    pub save_value_item: gtk::MenuItem,
+    pub replace_value_item: gtk::MenuItem,
+    pub explore_block_item: gtk::MenuItem,
+    key_repr_menu: ReprMenu,
+    value_repr_menu: ReprMenu,
 }
 
 impl CfView {
     pub fn new() -> Self {
-        const COLUMN_TYPES: [glib::Type; 2] = [glib::Type::STRING, glib::Type::STRING];
+        let column_types = [
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::U64,
+            glib::Type::U64,
+            glib::Bytes::static_type(),
+            glib::Bytes::static_type(),
+            glib::Type::U32,
+            glib::Type::BOOL,
+        ];
 
-        let store = gtk::ListStore::new(&COLUMN_TYPES);
+        let store = gtk::ListStore::new(&column_types);
         let tree_view = gtk::TreeView::with_model(&store);
         tree_view.set_vexpand(true);
+        tree_view.selection().set_mode(gtk::SelectionMode::Multiple);
+
+        let key_column = add_text_column(&tree_view, Column::Key, "Key");
+        let value_column = add_text_column(&tree_view, Column::Value, "Value");
+
+        // Values with a block id are shown as links
+        if let Some(renderer) = value_column.cells().first() {
+            TreeViewColumnExt::set_cell_data_func(
+                &value_column,
+                renderer,
+                Some(Box::new(|column, cell, model, iter| {
+                    let linked = model.value(iter, Column::Linked as i32).get::<bool>();
+                    let linked = linked.unwrap_or_default();
+                    let underline = if linked {
+                        gtk::pango::Underline::Single
+                    } else {
+                        gtk::pango::Underline::None
+                    };
+                    let color = column
+                        .tree_view()
+                        .filter(|_| linked)
+                        .map(|tree_view| link_color(&tree_view));
+                    cell.set_property("foreground-rgba", color.as_ref());
+                    cell.set_property("underline", underline);
+                })),
+            );
+        }
 
-        add_text_column(&tree_view, Column::Key, "Key");
-        add_text_column(&tree_view, Column::Value, "Value");
+        // Raw data columns, the value size is shown by default
+        let columns_menu = gtk::Menu::new();
+        for (column, title, visible) in [
+            (Column::KeyHex, "Key (hex)", false),
+            (Column::ValueHex, "Value (hex)", false),
+            (Column::KeySize, "Key size", false),
+            (Column::ValueSize, "Value size", true),
+        ] {
+            let view_column = add_text_column(&tree_view, column, title);
+            view_column.set_visible(visible);
+
+            let item = gtk::CheckMenuItem::with_label(title);
+            item.set_active(visible);
+            item.connect_toggled(move |item| view_column.set_visible(item.is_active()));
+            columns_menu.add(&item);
+        }
+        columns_menu.show_all();
 
-        Self { tree_view, store }
+        let rows = Rc::new(RefCell::new(Vec::<DecodedRow>::new()));
+
+        let key_repr_menu = ReprMenu::new(&key_column, &store, Column::Key);
+        let value_repr_menu = ReprMenu::new(&value_column, &store, Column::Value);
+        for (column, menu, part) in [
+            (Column::Key, &key_repr_menu, ReprColumn::Key),
+            (Column::Value, &value_repr_menu, ReprColumn::Value),
+        ] {
+            // Decoded values are sorted with numbers compared numerically
+            store.set_sort_func(gtk::SortColumn::Index(column as u32), {
+                let rows = rows.clone();
+                let sort_field = menu.sort_field.clone();
+                move |model, a, b| {
+                    let rows = rows.borrow();
+                    let sort_field = sort_field.borrow();
+                    let value = |iter: &gtk::TreeIter| {
+                        let row = model.value(iter, Column::Row as i32).get::<u32>().ok()?;
+                        let row = rows.get(row as usize)?;
+                        let value = match part {
+                            ReprColumn::Key => &row.key,
+                            ReprColumn::Value => &row.value,
+                        };
+                        match &*sort_field {
+                            Some(path) => value.field(path),
+                            None => Some(value.clone()),
+                        }
+                    };
+                    compare_fields(value(a), value(b))
+                }
+            });
+        }
+
+        let row_menu = RowMenu::new();
+        let copy_items = row_menu.copy_items.clone();
+        let save_value_item = row_menu.save_value_item.clone();
+        let replace_value_item = row_menu.replace_value_item.clone();
+        let explore_block_item = row_menu.explore_block_item.clone();
+        tree_view.connect_button_press_event({
+            let save_value_item = save_value_item.clone();
+            let replace_value_item = replace_value_item.clone();
+            let explore_block_item = explore_block_item.clone();
+            move |tree_view, event| {
+                if event.button() != gdk::BUTTON_SECONDARY {
+                    return Inhibit(false);
+                }
+
+                // Keep the multi-selection if the clicked row is a part of it
+                let (x, y) = event.position();
+                if let Some((Some(path), _, _, _)) = tree_view.path_at_pos(x as i32, y as i32) {
+                    let selection = tree_view.selection();
+                    if !selection.path_is_selected(&path) {
+                        selection.unselect_all();
+                        selection.select_path(&path);
+                    }
+                }
+
+                // Files are saved and loaded for one row at a time
+                let selected = tree_view.selection().count_selected_rows();
+                save_value_item.set_sensitive(selected == 1);
+                replace_value_item.set_sensitive(selected == 1);
+                explore_block_item.set_sensitive(selected == 1);
+                if selected > 0 {
+                    row_menu.menu.popup_at_pointer(Some(event));
+                }
+                Inhibit(true)
+            }
+        });
+
+        Self {
+            tree_view,
+            store,
+            columns_menu,
+            rows,
+            copy_items,
+            save_value_item,
+            replace_value_item,
+            explore_block_item,
+            key_repr_menu,
+            value_repr_menu,
+        }
     }
 
     pub fn update<I>(&self, iter: I)
     where
-        I: Iterator<Item = (String, String)>,
+        I: Iterator<Item = CfEntry>,
     {
+        self.clear();
+
+        // Sort once after loading instead of on every insert
+        let sort_column = self.store.sort_column_id();
+        self.store.set_unsorted();
+        for entry in iter {
+            self.append(entry);
+        }
+        if let Some((column, order)) = sort_column {
+            self.store.set_sort_column_id(column, order);
+        }
+    }
+
+    pub fn clear(&self) {
         self.store.clear();
-        for (key, value) in iter {
-            self.store.set(
-                &self.store.append(),
-                &[(Column::Key as u32, &key), (Column::Value as u32, &value)],
-            );
+        self.rows.borrow_mut().clear();
+    }
+
+    pub fn append(&self, entry: CfEntry) {
+        let row = {
+            let mut rows = self.rows.borrow_mut();
+            if rows.is_empty() {
+                self.key_repr_menu
+                    .set_sort_fields(entry.decoded_key.field_paths());
+                self.value_repr_menu
+                    .set_sort_fields(entry.decoded_value.field_paths());
+            }
+            rows.push(DecodedRow {
+                key: entry.decoded_key.clone(),
+                value: entry.decoded_value.clone(),
+            });
+            rows.len() as u32 - 1
+        };
+
+        // Huge values are read from the DB again when needed
+        let raw_value = (entry.value.len() <= MAX_STORED_VALUE_LEN)
+            .then(|| glib::Bytes::from(entry.value.as_ref()));
+
+        self.store.insert_with_values(
+            None,
+            &[
+                (Column::Key as u32, &entry.decoded_key.to_string()),
+                (Column::Value as u32, &entry.decoded_value.to_string()),
+                (Column::KeyHex as u32, &hex::encode(&entry.key)),
+                (Column::ValueHex as u32, &hex_preview(&entry.value)),
+                (Column::KeySize as u32, &(entry.key.len() as u64)),
+                (Column::ValueSize as u32, &(entry.value.len() as u64)),
+                (Column::RawKey as u32, &glib::Bytes::from_owned(entry.key)),
+                (Column::RawValue as u32, &raw_value),
+                (Column::Row as u32, &row),
+                (
+                    Column::Linked as u32,
+                    &entry.decoded_value.block_id().is_some(),
+                ),
+            ],
+        );
+    }
+
+    /// Selects the row with the specified raw key, if it is loaded.
+    pub fn select_key(&self, key: &[u8]) -> bool {
+        let Some(iter) = self.store.iter_first() else {
+            return false;
+        };
+
+        loop {
+            let row_key = self.store.value(&iter, Column::RawKey as i32);
+            if row_key.get::<glib::Bytes>().unwrap().as_ref() == key {
+                let selection = self.tree_view.selection();
+                selection.unselect_all();
+                selection.select_iter(&iter);
+                if let Some(path) = self.store.path(&iter) {
+                    self.tree_view.scroll_to_cell(
+                        Some(&path),
+                        None::<&gtk::TreeViewColumn>,
+                        false,
+                        0.0,
+                        0.0,
+                    );
+                }
+                return true;
+            }
+
+            if !self.store.iter_next(&iter) {
+                return false;
+            }
+        }
+    }
+
+    pub fn connect_row_selected<F>(&self, f: F)
+    where
+        F: Fn(Option<&[u8]>) + 'static,
+    {
+        // Details are shown only for a single selected row
+        self.tree_view
+            .selection()
+            .connect_changed(move |selection| {
+                let (paths, model) = selection.selected_rows();
+                match paths.as_slice() {
+                    [path] => {
+                        let Some(iter) = model.iter(path) else {
+                            return;
+                        };
+                        let key = model.value(&iter, Column::RawKey as i32);
+                        let key = key.get::<glib::Bytes>().unwrap();
+                        f(Some(&key));
+                    }
+                    _ => f(None),
+                }
+            });
+    }
+
+    /// Calls `f` with the block id in the value of an activated row.
+    ///
+    /// Links are followed on activation rather than on click to keep rows selectable.
+    pub fn connect_link_activated<F>(&self, f: F)
+    where
+        F: Fn(BlockIdFull) + 'static,
+    {
+        let store = self.store.clone();
+        let rows = self.rows.clone();
+        self.tree_view.connect_row_activated(move |_, path, _| {
+            let Some(iter) = store.iter(path) else {
+                return;
+            };
+            let row = store.value(&iter, Column::Row as i32).get::<u32>();
+            let block_id = row
+                .ok()
+                .and_then(|row| rows.borrow().get(row as usize)?.value.block_id());
+            if let Some(block_id) = block_id {
+                f(block_id);
+            }
+        });
+    }
+
+    /// Shows the actions that modify the DB.
+    pub fn set_writable(&self, writable: bool) {
+        self.replace_value_item.set_visible(writable);
+    }
+
+    /// Returns the data of the selected rows in the table order.
+    pub fn selected_rows(&self) -> Vec<SelectedRow> {
+        let (paths, model) = self.tree_view.selection().selected_rows();
+        paths
+            .iter()
+            .filter_map(|path| {
+                let iter = model.iter(path)?;
+                let bytes = |column: Column| {
+                    let value = model.value(&iter, column as i32);
+                    let bytes = value.get::<Option<glib::Bytes>>().ok()??;
+                    Some(bytes.to_vec())
+                };
+                let text = |column: Column| {
+                    let value = model.value(&iter, column as i32);
+                    value.get::<String>().unwrap_or_default()
+                };
+                Some(SelectedRow {
+                    key: bytes(Column::RawKey)?,
+                    value: bytes(Column::RawValue),
+                    decoded_key: text(Column::Key),
+                    decoded_value: text(Column::Value),
+                })
+            })
+            .collect()
+    }
+
+    /// Formats the visible columns of the selected rows as tab separated values.
+    pub fn selected_rows_tsv(&self) -> String {
+        let columns = self
+            .tree_view
+            .columns()
+            .into_iter()
+            .filter(|column| column.is_visible())
+            .map(|column| column.sort_column_id())
+            .collect::<Vec<_>>();
+
+        let (paths, model) = self.tree_view.selection().selected_rows();
+        let mut result = String::new();
+        for path in paths {
+            let Some(iter) = model.iter(&path) else {
+                continue;
+            };
+            let fields = columns
+                .iter()
+                .map(|&column| {
+                    let value = model.value(&iter, column);
+                    match value.get::<u64>() {
+                        Ok(number) => number.to_string(),
+                        Err(_) => tsv_field(&value.get::<String>().unwrap_or_default()),
+                    }
+                })
+                .collect::<Vec<_>>();
+            result.push_str(&fields.join("\t"));
+            result.push('\n');
         }
+        result
+    }
+
+    /// Called from the row context menu and on Ctrl+C, which copies the rows as TSV.
+    pub fn connect_copy<F>(&self, f: F)
+    where
+        F: Fn(CopyContent) + 'static,
+    {
+        let f = Rc::new(f);
+        for (content, item) in &self.copy_items {
+            let content = *content;
+            let f = f.clone();
+            item.connect_activate(move |_| f(content));
+        }
+
+        self.tree_view.connect_key_press_event(move |_, event| {
+            let ctrl = event.state().contains(gdk::ModifierType::CONTROL_MASK);
+            if ctrl && event.keyval() == gdk::keys::constants::c {
+                f(CopyContent::Rows);
+                Inhibit(true)
+            } else {
+                Inhibit(false)
+            }
+        });
+    }
+
+    pub fn set_reprs(&self, key_repr: ReprKind, value_repr: ReprKind) {
+        self.key_repr_menu.set_active(key_repr);
+        self.value_repr_menu.set_active(value_repr);
+    }
+
+    pub fn connect_repr_selected<F>(&self, f: F)
+    where
+        F: Fn(ReprColumn, ReprKind) + 'static,
+    {
+        let f = Rc::new(f);
+        self.key_repr_menu.connect_selected({
+            let f = f.clone();
+            move |repr| f(ReprColumn::Key, repr)
+        });
+        self.value_repr_menu
+            .connect_selected(move |repr| f(ReprColumn::Value, repr));
     }
 }
 
@@ -40,19 +416,265 @@ impl AsRef<gtk::TreeView> for CfView {
     }
 }
 
-fn add_text_column(tree_view: &gtk::TreeView, column: Column, title: &str) {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReprColumn {
+    Key,
+    Value,
+}
+
+/// Data of a selected row.
+pub struct SelectedRow {
+    pub key: Vec<u8>,
+    /// `None` if the value is too large to be kept in the table.
+    pub value: Option<Vec<u8>>,
+    pub decoded_key: String,
+    pub decoded_value: String,
+}
+
+/// Raw values up to this size are kept in the model.
+const MAX_STORED_VALUE_LEN: usize = 64 * 1024;
+
+fn hex_preview(data: &[u8]) -> String {
+    const PREVIEW_LEN: usize = 32;
+
+    match data.get(..PREVIEW_LEN) {
+        Some(prefix) if data.len() > PREVIEW_LEN => format!("{}…", hex::encode(prefix)),
+        _ => hex::encode(data),
+    }
+}
+
+/// Context menu of the table rows.
+struct RowMenu {
+    menu: gtk::Menu,
+    copy_items: Vec<(CopyContent, gtk::MenuItem)>,
+    save_value_item: gtk::MenuItem,
+    replace_value_item: gtk::MenuItem,
+    explore_block_item: gtk::MenuItem,
+}
+
+impl RowMenu {
+    fn new() -> Self {
+        let menu = gtk::Menu::new();
+        let mut items = Vec::new();
+
+        for (title, part) in [
+            ("Copy key", ReprColumn::Key),
+            ("Copy value", ReprColumn::Value),
+        ] {
+            let submenu = gtk::Menu::new();
+            for format in CopyFormat::ALL {
+                let item = gtk::MenuItem::with_label(format.name());
+                submenu.add(&item);
+                let content = match part {
+                    ReprColumn::Key => CopyContent::Key(format),
+                    ReprColumn::Value => CopyContent::Value(format),
+                };
+                items.push((content, item));
+            }
+
+            let item = gtk::MenuItem::with_label(title);
+            item.set_submenu(Some(&submenu));
+            menu.add(&item);
+        }
+
+        let item = gtk::MenuItem::with_label("Copy rows as TSV");
+        menu.add(&gtk::SeparatorMenuItem::new());
+        menu.add(&item);
+        items.push((CopyContent::Rows, item));
+
+        let save_value_item = gtk::MenuItem::with_label("Save value as…");
+        let replace_value_item = gtk::MenuItem::with_label("Replace value from file…");
+        menu.add(&gtk::SeparatorMenuItem::new());
+        menu.add(&save_value_item);
+        menu.add(&replace_value_item);
+
+        let explore_block_item = gtk::MenuItem::with_label("Explore block");
+        menu.add(&gtk::SeparatorMenuItem::new());
+        menu.add(&explore_block_item);
+
+        menu.show_all();
+        replace_value_item.hide();
+        replace_value_item.set_no_show_all(true);
+
+        Self {
+            menu,
+            copy_items: items,
+            save_value_item,
+            replace_value_item,
+            explore_block_item,
+        }
+    }
+}
+
+/// Decoded values kept for sorting.
+struct DecodedRow {
+    key: Value,
+    value: Value,
+}
+
+/// Rows without the sort field go last.
+fn compare_fields(left: Option<Value>, right: Option<Value>) -> Ordering {
+    match (left, right) {
+        (Some(left), Some(right)) => left.compare(&right),
+        (left, right) => left.is_none().cmp(&right.is_none()),
+    }
+}
+
+/// Decoder and sort field chooser shown on right click on a column header.
+struct ReprMenu {
+    items: Vec<(ReprKind, gtk::RadioMenuItem)>,
+    updating: Rc<Cell<bool>>,
+    sort_field: Rc<RefCell<Option<String>>>,
+    sort_field_menu: gtk::Menu,
+    store: gtk::ListStore,
+    sort_column: Column,
+}
+
+impl ReprMenu {
+    fn new(column: &gtk::TreeViewColumn, store: &gtk::ListStore, sort_column: Column) -> Self {
+        let menu = gtk::Menu::new();
+
+        let mut items = Vec::<(ReprKind, gtk::RadioMenuItem)>::new();
+        for repr in ReprKind::ALL {
+            let item = match items.first() {
+                Some((_, group)) => {
+                    gtk::RadioMenuItem::with_label_from_widget(group, Some(repr.name()))
+                }
+                None => gtk::RadioMenuItem::with_label(repr.name()),
+            };
+            menu.add(&item);
+            items.push((repr, item));
+        }
+
+        let sort_field_menu = gtk::Menu::new();
+        let sort_field_item = gtk::MenuItem::with_label("Sort by field");
+        sort_field_item.set_submenu(Some(&sort_field_menu));
+        menu.add(&gtk::SeparatorMenuItem::new());
+        menu.add(&sort_field_item);
+        menu.show_all();
+
+        column.set_clickable(true);
+        if let Some(button) = column.button() {
+            button.connect_button_press_event(move |_, event| {
+                if event.button() == gdk::BUTTON_SECONDARY {
+                    menu.popup_at_pointer(Some(event));
+                    Inhibit(true)
+                } else {
+                    Inhibit(false)
+                }
+            });
+        }
+
+        Self {
+            items,
+            updating: Default::default(),
+            sort_field: Default::default(),
+            sort_field_menu,
+            store: store.clone(),
+            sort_column,
+        }
+    }
+
+    fn set_active(&self, repr: ReprKind) {
+        self.updating.set(true);
+        for (item_repr, item) in &self.items {
+            if *item_repr == repr {
+                item.set_active(true);
+            }
+        }
+        self.updating.set(false);
+    }
+
+    fn connect_selected<F>(&self, f: F)
+    where
+        F: Fn(ReprKind) + 'static,
+    {
+        let f = Rc::new(f);
+        for (repr, item) in &self.items {
+            let repr = *repr;
+            let f = f.clone();
+            let updating = self.updating.clone();
+            item.connect_toggled(move |item| {
+                if item.is_active() && !updating.get() {
+                    f(repr);
+                }
+            });
+        }
+    }
+
+    /// Rebuilds the sort field list for the current decoder.
+    fn set_sort_fields(&self, paths: Vec<String>) {
+        for item in self.sort_field_menu.children() {
+            self.sort_field_menu.remove(&item);
+        }
+
+        let selected = self.sort_field.borrow().clone();
+        if selected.is_some_and(|path| !paths.contains(&path)) {
+            self.sort_field.borrow_mut().take();
+        }
+
+        let whole_value = gtk::RadioMenuItem::with_label("Whole value");
+        let mut items = vec![(None, whole_value.clone())];
+        for path in paths {
+            let item = gtk::RadioMenuItem::with_label_from_widget(&whole_value, Some(&path));
+            items.push((Some(path), item));
+        }
+
+        for (path, item) in items {
+            item.set_active(*self.sort_field.borrow() == path);
+            item.connect_toggled({
+                let sort_field = self.sort_field.clone();
+                let store = self.store.clone();
+                let sort_column = gtk::SortColumn::Index(self.sort_column as u32);
+                move |item| {
+                    if item.is_active() {
+                        *sort_field.borrow_mut() = path.clone();
+
+                        // Resort even if the column is already sorted
+                        store.set_unsorted();
+                        store.set_sort_column_id(sort_column, gtk::SortType::Ascending);
+                    }
+                }
+            });
+            self.sort_field_menu.add(&item);
+        }
+        self.sort_field_menu.show_all();
+    }
+}
+
+fn add_text_column(tree_view: &gtk::TreeView, column: Column, title: &str) -> gtk::TreeViewColumn {
     let renderer = gtk::CellRendererText::new();
     let view_column = gtk::TreeViewColumn::new();
     view_column.set_resizable(true);
     view_column.set_title(title);
     TreeViewColumnExt::pack_start(&view_column, &renderer, true);
     TreeViewColumnExt::add_attribute(&view_column, &renderer, "text", column as i32);
+    view_column.set_sort_column_id(column as i32);
     tree_view.append_column(&view_column);
+    view_column
+}
+
+/// Link color of the current theme.
+fn link_color(widget: &gtk::Widget) -> gdk::RGBA {
+    let style_context = widget.style_context();
+    style_context.save();
+    style_context.set_state(gtk::StateFlags::LINK);
+    let color = style_context.color(gtk::StateFlags::LINK);
+    style_context.restore();
+    color
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 #[repr(i32)]
 enum Column {
     Key,
     Value,
+    KeyHex,
+    ValueHex,
+    KeySize,
+    ValueSize,
+    RawKey,
+    RawValue,
+    Row,
+    Linked,
 }
@@ -0,0 +1,420 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use gtk::prelude::*;
+use gtk::{cairo, gdk};
+
+use crate::controller::{BlockIdFull, ChainGraph};
+
+const NODE_WIDTH: f64 = 170.0;
+const NODE_HEIGHT: f64 = 36.0;
+const COLUMN_STEP: f64 = 210.0;
+const ROW_STEP: f64 = 56.0;
+const MARGIN: f64 = 20.0;
+
+const ZOOM_STEP: f64 = 1.25;
+const MIN_ZOOM: f64 = 0.2;
+const MAX_ZOOM: f64 = 4.0;
+/// Largest surface cairo can draw on.
+const MAX_SURFACE_SIZE: f64 = 32767.0;
+
+/// Page drawing the blocks around one block, one row per shard and one column per seqno.
+pub struct ChainGraphView {
+    container: gtk::Box,
+    entry: gtk::Entry,
+    depth_spin: gtk::SpinButton,
+    show_btn: gtk::Button,
+    info_label: gtk::Label,
+    zoom_label: gtk::Label,
+    drawing_area: gtk::DrawingArea,
+    layout: Rc<RefCell<Layout>>,
+    zoom: Rc<Cell<f64>>,
+}
+
+#[derive(Default)]
+struct Layout {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    width: f64,
+    height: f64,
+}
+
+struct Node {
+    id: BlockIdFull,
+    x: f64,
+    y: f64,
+    missing: bool,
+}
+
+struct Edge {
+    from: (f64, f64),
+    to: (f64, f64),
+    state: EdgeState,
+}
+
+enum EdgeState {
+    Complete,
+    /// Stored in one direction only.
+    Broken,
+    /// Leads to a block without rows.
+    Missing,
+}
+
+impl ChainGraphView {
+    pub fn new() -> Self {
+        let entry = gtk::Entry::new();
+        entry.set_placeholder_text(Some("workchain:shard:seqno[:root_hash:file_hash]"));
+        let depth_spin = gtk::SpinButton::with_range(1.0, 50.0, 1.0);
+        depth_spin.set_value(5.0);
+        let show_btn = gtk::Button::with_label("Show");
+
+        let zoom_out_btn = gtk::Button::from_icon_name(Some("zoom-out"), gtk::IconSize::Button);
+        zoom_out_btn.set_tooltip_text(Some("Zoom out (Ctrl+scroll)"));
+        let zoom_in_btn = gtk::Button::from_icon_name(Some("zoom-in"), gtk::IconSize::Button);
+        zoom_in_btn.set_tooltip_text(Some("Zoom in (Ctrl+scroll)"));
+        let zoom_label = gtk::Label::new(Some("100%"));
+        zoom_label.set_width_chars(5);
+
+        let input_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        input_box.set_border_width(4);
+        input_box.pack_start(&gtk::Label::new(Some("Block:")), false, false, 0);
+        input_box.pack_start(&entry, true, true, 0);
+        input_box.pack_start(&gtk::Label::new(Some("Depth:")), false, false, 0);
+        input_box.pack_start(&depth_spin, false, false, 0);
+        input_box.pack_start(&show_btn, false, false, 0);
+        input_box.pack_start(&zoom_out_btn, false, false, 0);
+        input_box.pack_start(&zoom_label, false, false, 0);
+        input_box.pack_start(&zoom_in_btn, false, false, 0);
+
+        let info_label = gtk::Label::new(None);
+        info_label.set_xalign(0.0);
+        info_label.set_margin_start(4);
+
+        let layout = Rc::new(RefCell::new(Layout::default()));
+        let zoom = Rc::new(Cell::new(1.0));
+
+        let drawing_area = gtk::DrawingArea::new();
+        drawing_area.add_events(gdk::EventMask::BUTTON_PRESS_MASK | gdk::EventMask::SCROLL_MASK);
+        drawing_area.set_has_tooltip(true);
+        drawing_area.connect_draw({
+            let layout = layout.clone();
+            let zoom = zoom.clone();
+            move |_, cr| {
+                cr.scale(zoom.get(), zoom.get());
+                // Errors only mean the surface is unusable, there is nothing to recover
+                layout.borrow().draw(cr).ok();
+                Inhibit(false)
+            }
+        });
+        drawing_area.connect_query_tooltip({
+            let layout = layout.clone();
+            let zoom = zoom.clone();
+            move |_, x, y, _, tooltip| {
+                let zoom = zoom.get();
+                match layout.borrow().node_at(x as f64 / zoom, y as f64 / zoom) {
+                    Some(id) => {
+                        tooltip.set_text(Some(&id.to_string()));
+                        true
+                    }
+                    None => false,
+                }
+            }
+        });
+
+        let set_zoom = Rc::new({
+            let drawing_area = drawing_area.clone();
+            let zoom_label = zoom_label.clone();
+            let layout = layout.clone();
+            let zoom = zoom.clone();
+            move |value: f64| {
+                let layout = layout.borrow();
+                let value = value.clamp(MIN_ZOOM, MAX_ZOOM).min(layout.max_zoom());
+                zoom.set(value);
+                zoom_label.set_text(&format!("{:.0}%", value * 100.0));
+                layout.resize(&drawing_area, value);
+            }
+        });
+        zoom_in_btn.connect_clicked({
+            let set_zoom = set_zoom.clone();
+            let zoom = zoom.clone();
+            move |_| set_zoom(zoom.get() * ZOOM_STEP)
+        });
+        zoom_out_btn.connect_clicked({
+            let set_zoom = set_zoom.clone();
+            let zoom = zoom.clone();
+            move |_| set_zoom(zoom.get() / ZOOM_STEP)
+        });
+        drawing_area.connect_scroll_event({
+            let zoom = zoom.clone();
+            move |_, event| {
+                if !event.state().contains(gdk::ModifierType::CONTROL_MASK) {
+                    return Inhibit(false);
+                }
+                match event.direction() {
+                    gdk::ScrollDirection::Up => set_zoom(zoom.get() * ZOOM_STEP),
+                    gdk::ScrollDirection::Down => set_zoom(zoom.get() / ZOOM_STEP),
+                    _ => {}
+                }
+                Inhibit(true)
+            }
+        });
+
+        let scrolled_window =
+            gtk::ScrolledWindow::new(gtk::Adjustment::NONE, gtk::Adjustment::NONE);
+        scrolled_window.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
+        scrolled_window.add(&drawing_area);
+
+        let container = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        container.pack_start(&input_box, false, false, 0);
+        container.pack_start(&info_label, false, false, 0);
+        container.pack_start(&scrolled_window, true, true, 0);
+
+        Self {
+            container,
+            entry,
+            depth_spin,
+            show_btn,
+            info_label,
+            zoom_label,
+            drawing_area,
+            layout,
+            zoom,
+        }
+    }
+
+    /// Calls `f` with the entered block id and depth.
+    pub fn connect_show<F>(&self, f: F)
+    where
+        F: Fn(&str, u32) + 'static,
+    {
+        let f = Rc::new(f);
+        self.entry.connect_activate({
+            let f = f.clone();
+            let depth_spin = self.depth_spin.clone();
+            move |entry| f(entry.text().trim(), depth_spin.value_as_int() as u32)
+        });
+        self.show_btn.connect_clicked({
+            let entry = self.entry.clone();
+            let depth_spin = self.depth_spin.clone();
+            move |_| f(entry.text().trim(), depth_spin.value_as_int() as u32)
+        });
+    }
+
+    /// Calls `f` with the id of a clicked block.
+    pub fn connect_node_activated<F>(&self, f: F)
+    where
+        F: Fn(&BlockIdFull) + 'static,
+    {
+        let layout = self.layout.clone();
+        let zoom = self.zoom.clone();
+        self.drawing_area
+            .connect_button_press_event(move |_, event| {
+                let click = event.button() == gdk::BUTTON_PRIMARY
+                    && event.event_type() == gdk::EventType::ButtonPress;
+                if click {
+                    let (x, y) = event.position();
+                    let zoom = zoom.get();
+                    let id = layout.borrow().node_at(x / zoom, y / zoom);
+                    if let Some(id) = id {
+                        f(&id);
+                    }
+                }
+                Inhibit(false)
+            });
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth_spin.value_as_int() as u32
+    }
+
+    pub fn set_block_text(&self, text: &str) {
+        self.entry.set_text(text);
+    }
+
+    pub fn show(&self, graph: ChainGraph) {
+        let broken = graph
+            .edges
+            .iter()
+            .filter(|edge| !edge.missing && !edge.is_complete())
+            .count();
+        let missing = graph.nodes.iter().filter(|node| node.missing).count();
+        let mut info = format!("{} blocks, {} links", graph.nodes.len(), graph.edges.len());
+        if broken > 0 {
+            info.push_str(&format!(", {broken} stored in one direction only (red)"));
+        }
+        if missing > 0 {
+            info.push_str(&format!(", {missing} linked blocks not stored (dashed)"));
+        }
+        self.info_label.set_text(&info);
+
+        self.set_layout(Layout::new(&graph));
+    }
+
+    pub fn clear(&self) {
+        self.info_label.set_text("");
+        self.set_layout(Layout::default());
+    }
+
+    /// Lowers the zoom if the new graph would not fit on a surface at the current one.
+    fn set_layout(&self, layout: Layout) {
+        let zoom = self.zoom.get().min(layout.max_zoom());
+        self.zoom.set(zoom);
+        self.zoom_label.set_text(&format!("{:.0}%", zoom * 100.0));
+        layout.resize(&self.drawing_area, zoom);
+        *self.layout.borrow_mut() = layout;
+    }
+}
+
+impl AsRef<gtk::Box> for ChainGraphView {
+    fn as_ref(&self) -> &gtk::Box {
+        &self.container
+    }
+}
+
+impl Layout {
+    /// Places shards in the order of their ids, so children of a split surround their parent.
+    fn new(graph: &ChainGraph) -> Self {
+        let mut shards = graph
+            .nodes
+            .iter()
+            .map(|node| (node.id.workchain, node.id.shard))
+            .collect::<Vec<_>>();
+        shards.sort_unstable();
+        shards.dedup();
+        let min_seqno = graph
+            .nodes
+            .iter()
+            .map(|node| node.id.seqno)
+            .min()
+            .unwrap_or_default();
+
+        let nodes = graph
+            .nodes
+            .iter()
+            .map(|node| {
+                let id = &node.id;
+                let row = shards.binary_search(&(id.workchain, id.shard)).unwrap();
+                Node {
+                    id: *id,
+                    x: MARGIN + (id.seqno - min_seqno) as f64 * COLUMN_STEP,
+                    y: MARGIN + row as f64 * ROW_STEP,
+                    missing: node.missing,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let edges = graph
+            .edges
+            .iter()
+            .map(|edge| {
+                let older = &nodes[edge.older];
+                let newer = &nodes[edge.newer];
+                Edge {
+                    from: (older.x + NODE_WIDTH, older.y + NODE_HEIGHT / 2.0),
+                    to: (newer.x, newer.y + NODE_HEIGHT / 2.0),
+                    state: if edge.missing {
+                        EdgeState::Missing
+                    } else if edge.is_complete() {
+                        EdgeState::Complete
+                    } else {
+                        EdgeState::Broken
+                    },
+                }
+            })
+            .collect();
+
+        let width = nodes.iter().map(|node| node.x).fold(0.0, f64::max);
+        let height = nodes.iter().map(|node| node.y).fold(0.0, f64::max);
+        Self {
+            nodes,
+            edges,
+            width: width + NODE_WIDTH + MARGIN,
+            height: height + NODE_HEIGHT + MARGIN,
+        }
+    }
+
+    /// Largest zoom at which the graph fits on a surface, may be below [`MIN_ZOOM`].
+    fn max_zoom(&self) -> f64 {
+        MAX_ZOOM.min(MAX_SURFACE_SIZE / self.width.max(self.height))
+    }
+
+    fn resize(&self, drawing_area: &gtk::DrawingArea, zoom: f64) {
+        drawing_area.set_size_request(
+            (self.width * zoom).ceil() as i32,
+            (self.height * zoom).ceil() as i32,
+        );
+        drawing_area.queue_draw();
+    }
+
+    fn node_at(&self, x: f64, y: f64) -> Option<BlockIdFull> {
+        self.nodes
+            .iter()
+            .find(|node| {
+                (node.x..node.x + NODE_WIDTH).contains(&x)
+                    && (node.y..node.y + NODE_HEIGHT).contains(&y)
+            })
+            .map(|node| node.id)
+    }
+
+    fn draw(&self, cr: &cairo::Context) -> Result<(), cairo::Error> {
+        cr.set_line_width(1.5);
+        for edge in &self.edges {
+            match edge.state {
+                EdgeState::Complete => {
+                    cr.set_source_rgb(0.5, 0.5, 0.5);
+                    cr.set_dash(&[], 0.0);
+                }
+                EdgeState::Broken => {
+                    cr.set_source_rgb(0.85, 0.1, 0.1);
+                    cr.set_dash(&[6.0, 4.0], 0.0);
+                }
+                EdgeState::Missing => {
+                    cr.set_source_rgb(0.5, 0.5, 0.5);
+                    cr.set_dash(&[2.0, 3.0], 0.0);
+                }
+            }
+            let ((x1, y1), (x2, y2)) = (edge.from, edge.to);
+            let bend = (x2 - x1) / 2.0;
+            cr.move_to(x1, y1);
+            cr.curve_to(x1 + bend, y1, x2 - bend, y2, x2, y2);
+            cr.stroke()?;
+        }
+
+        cr.select_font_face(
+            "monospace",
+            cairo::FontSlant::Normal,
+            cairo::FontWeight::Normal,
+        );
+        cr.set_font_size(11.0);
+        for (i, node) in self.nodes.iter().enumerate() {
+            // The first node is the requested block, missing ones are placeholders
+            if i == 0 {
+                cr.set_source_rgb(1.0, 0.88, 0.55);
+            } else if node.missing {
+                cr.set_source_rgb(0.96, 0.96, 0.96);
+            } else {
+                cr.set_source_rgb(0.9, 0.93, 1.0);
+            }
+            cr.rectangle(node.x, node.y, NODE_WIDTH, NODE_HEIGHT);
+            cr.fill_preserve()?;
+            cr.set_source_rgb(0.3, 0.3, 0.4);
+            if node.missing {
+                cr.set_dash(&[4.0, 3.0], 0.0);
+            } else {
+                cr.set_dash(&[], 0.0);
+            }
+            cr.stroke()?;
+
+            cr.set_source_rgb(0.0, 0.0, 0.0);
+            cr.move_to(node.x + 6.0, node.y + 15.0);
+            cr.show_text(&format!("{}:{:016x}", node.id.workchain, node.id.shard))?;
+            cr.move_to(node.x + 6.0, node.y + 29.0);
+            if node.missing {
+                cr.show_text(&format!("seqno {} (missing)", node.id.seqno))?;
+            } else {
+                cr.show_text(&format!("seqno {}", node.id.seqno))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use base64::Engine as _;
+use gtk::gdk;
+
+/// Part of the selected rows copied to the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyContent {
+    Key(CopyFormat),
+    Value(CopyFormat),
+    /// Visible columns of the selected rows, tab separated.
+    Rows,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyFormat {
+    Decoded,
+    Hex,
+    Base64,
+    ByteLiteral,
+}
+
+impl CopyFormat {
+    pub const ALL: [Self; 4] = [Self::Decoded, Self::Hex, Self::Base64, Self::ByteLiteral];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Decoded => "Decoded",
+            Self::Hex => "Hex",
+            Self::Base64 => "Base64",
+            Self::ByteLiteral => "Byte literal",
+        }
+    }
+
+    /// Whether the raw bytes are needed to format the data.
+    pub fn is_raw(self) -> bool {
+        self != Self::Decoded
+    }
+
+    pub fn format(self, data: &[u8], decoded: &str) -> String {
+        match self {
+            Self::Decoded => decoded.to_owned(),
+            Self::Hex => hex::encode(data),
+            Self::Base64 => base64::engine::general_purpose::STANDARD.encode(data),
+            Self::ByteLiteral => byte_literal(data),
+        }
+    }
+}
+
+/// Formats bytes as an escaped `b"..."` literal.
+pub fn byte_literal(data: &[u8]) -> String {
+    let mut result = String::with_capacity(data.len() + 3);
+    result.push_str("b\"");
+    for &byte in data {
+        result.extend(std::ascii::escape_default(byte).map(char::from));
+    }
+    result.push('"');
+    result
+}
+
+/// Escapes a cell so that it doesn't break the TSV layout.
+pub fn tsv_field(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+pub fn set_clipboard_text(text: &str) {
+    gtk::Clipboard::get(&gdk::SELECTION_CLIPBOARD).set_text(text);
+}
@@ -0,0 +1,374 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::prelude::*;
+use gtk::{gdk, glib};
+
+use super::CellTree;
+use crate::controller::{CellSource, Value, ValueKind};
+
+pub struct DetailView {
+    container: gtk::Paned,
+    fields_view: gtk::TreeView,
+    fields_store: gtk::TreeStore,
+    dump: HexDump,
+    cell_tree: CellTree,
+    data: Rc<RefCell<DetailData>>,
+    save_bytes_item: gtk::MenuItem,
+}
+
+impl DetailView {
+    pub fn new() -> Self {
+        let column_types = [
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::I32,
+            glib::Type::U64,
+            glib::Type::U64,
+        ];
+
+        let fields_store = gtk::TreeStore::new(&column_types);
+        let fields_view = gtk::TreeView::with_model(&fields_store);
+        add_text_column(&fields_view, Column::Name, "Field");
+        add_text_column(&fields_view, Column::Value, "Value");
+
+        let fields_page = gtk::ScrolledWindow::new(gtk::Adjustment::NONE, gtk::Adjustment::NONE);
+        fields_page.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
+        fields_page.set_width_request(300);
+        fields_page.add(&fields_view);
+
+        let dump = HexDump::new();
+        let dump_page = gtk::ScrolledWindow::new(gtk::Adjustment::NONE, gtk::Adjustment::NONE);
+        dump_page.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
+        dump_page.add(&dump.view);
+
+        // Cells are shown only for values which are bags of cells
+        let cell_tree = CellTree::new();
+        let notebook = gtk::Notebook::new();
+        notebook.append_page(&dump_page, Some(&gtk::Label::new(Some("Hex"))));
+        notebook.append_page(cell_tree.as_ref(), Some(&gtk::Label::new(Some("Cells"))));
+        notebook.show_all();
+        cell_tree.as_ref().hide();
+        cell_tree.as_ref().set_no_show_all(true);
+
+        let container = gtk::Paned::new(gtk::Orientation::Horizontal);
+        container.pack1(&fields_page, true, false);
+        container.pack2(&notebook, true, false);
+
+        let data = Rc::new(RefCell::new(DetailData::default()));
+
+        // Byte ranges of fields, e.g. archive entries, can be saved to a file
+        let field_menu = gtk::Menu::new();
+        let save_bytes_item = gtk::MenuItem::with_label("Save bytes as…");
+        field_menu.add(&save_bytes_item);
+        field_menu.show_all();
+
+        fields_view.connect_button_press_event(move |fields_view, event| {
+            if event.button() != gdk::BUTTON_SECONDARY {
+                return Inhibit(false);
+            }
+
+            let (x, y) = event.position();
+            let Some((Some(path), _, _, _)) = fields_view.path_at_pos(x as i32, y as i32) else {
+                return Inhibit(false);
+            };
+            fields_view.selection().select_path(&path);
+            field_menu.popup_at_pointer(Some(event));
+            Inhibit(true)
+        });
+
+        fields_view.selection().connect_changed({
+            let dump = dump.clone();
+            let data = data.clone();
+            move |selection| {
+                let Some((model, iter)) = selection.selected() else {
+                    return;
+                };
+
+                let part = match model.value(&iter, Column::Part as i32).get::<i32>() {
+                    Ok(part) if part == Part::Key as i32 => Part::Key,
+                    _ => Part::Value,
+                };
+                let start = model
+                    .value(&iter, Column::Start as i32)
+                    .get::<u64>()
+                    .unwrap();
+                let end = model.value(&iter, Column::End as i32).get::<u64>().unwrap();
+
+                let mut data = data.borrow_mut();
+                if data.shown != Some(part) {
+                    data.shown = Some(part);
+                    dump.set_data(data.part(part));
+                }
+                dump.highlight(start as usize, end as usize);
+            }
+        });
+
+        Self {
+            container,
+            fields_view,
+            fields_store,
+            dump,
+            cell_tree,
+            data,
+            save_bytes_item,
+        }
+    }
+
+    /// Adds the cell tree of the shown value.
+    pub fn show_cells(&self, source: Rc<dyn CellSource>, roots: &[[u8; 32]]) {
+        self.cell_tree.show(source, roots);
+        self.cell_tree.as_ref().show();
+    }
+
+    /// Called with the field name and bytes of the selected field.
+    pub fn connect_save_bytes<F>(&self, f: F)
+    where
+        F: Fn(&str, &[u8]) + 'static,
+    {
+        let fields_view = self.fields_view.clone();
+        let data = self.data.clone();
+        self.save_bytes_item.connect_activate(move |_| {
+            let Some((model, iter)) = fields_view.selection().selected() else {
+                return;
+            };
+
+            let name = model.value(&iter, Column::Name as i32);
+            let name = name.get::<&str>().unwrap_or_default();
+            let part = match model.value(&iter, Column::Part as i32).get::<i32>() {
+                Ok(part) if part == Part::Key as i32 => Part::Key,
+                _ => Part::Value,
+            };
+            let start = model.value(&iter, Column::Start as i32).get::<u64>();
+            let end = model.value(&iter, Column::End as i32).get::<u64>();
+
+            let data = data.borrow();
+            let (Ok(start), Ok(end)) = (start, end) else {
+                return;
+            };
+            if let Some(bytes) = data.part(part).get(start as usize..end as usize) {
+                f(name, bytes);
+            }
+        });
+    }
+
+    /// Shows the entry with its already decoded key and value.
+    pub fn show(&self, key: &[u8], decoded_key: &Value, value: Option<(&[u8], &Value)>) {
+        self.clear();
+
+        self.add_value(None, Part::Key, "key", decoded_key);
+
+        let shown = match value {
+            Some((value, decoded_value)) => {
+                self.add_value(None, Part::Value, "value", decoded_value);
+                self.dump.set_data(value);
+                Part::Value
+            }
+            None => {
+                self.fields_store.insert_with_values(
+                    None,
+                    None,
+                    &[
+                        (Column::Name as u32, &"value"),
+                        (Column::Value as u32, &"<not found>"),
+                    ],
+                );
+                self.dump.set_data(key);
+                Part::Key
+            }
+        };
+        self.fields_view.expand_all();
+
+        *self.data.borrow_mut() = DetailData {
+            key: key.to_vec(),
+            value: value.map(|(value, _)| value.to_vec()).unwrap_or_default(),
+            shown: Some(shown),
+        };
+    }
+
+    pub fn clear(&self) {
+        self.fields_store.clear();
+        self.dump.set_data(&[]);
+        self.cell_tree.clear();
+        self.cell_tree.as_ref().hide();
+        *self.data.borrow_mut() = DetailData::default();
+    }
+
+    fn add_value(&self, parent: Option<&gtk::TreeIter>, part: Part, name: &str, value: &Value) {
+        let text = match &value.kind {
+            ValueKind::Error { offset, reason } => format!("<invalid at byte {offset}: {reason}>"),
+            _ => value.to_string(),
+        };
+
+        let iter = self.fields_store.insert_with_values(
+            parent,
+            None,
+            &[
+                (Column::Name as u32, &name),
+                (Column::Value as u32, &text),
+                (Column::Part as u32, &(part as i32)),
+                (Column::Start as u32, &(value.range.start as u64)),
+                (Column::End as u32, &(value.range.end as u64)),
+            ],
+        );
+
+        for (name, child) in value.children() {
+            self.add_value(Some(&iter), part, &name, &child);
+        }
+    }
+}
+
+impl AsRef<gtk::Paned> for DetailView {
+    fn as_ref(&self) -> &gtk::Paned {
+        &self.container
+    }
+}
+
+#[derive(Default)]
+struct DetailData {
+    key: Vec<u8>,
+    value: Vec<u8>,
+    shown: Option<Part>,
+}
+
+impl DetailData {
+    fn part(&self, part: Part) -> &[u8] {
+        match part {
+            Part::Key => &self.key,
+            Part::Value => &self.value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    Key,
+    Value,
+}
+
+/// Classic offset, hex and ASCII dump with a highlighted byte range.
+#[derive(Clone)]
+struct HexDump {
+    view: gtk::TextView,
+    buffer: gtk::TextBuffer,
+}
+
+impl HexDump {
+    const BYTES_PER_LINE: usize = 16;
+    const MAX_LEN: usize = 64 * 1024;
+    const HIGHLIGHT_TAG: &str = "highlight";
+
+    fn new() -> Self {
+        let highlight = gtk::TextTag::new(Some(Self::HIGHLIGHT_TAG));
+        highlight.set_background(Some("#ffe08a"));
+
+        let tag_table = gtk::TextTagTable::new();
+        tag_table.add(&highlight);
+
+        let buffer = gtk::TextBuffer::new(Some(&tag_table));
+        let view = gtk::TextView::with_buffer(&buffer);
+        view.set_editable(false);
+        view.set_monospace(true);
+
+        Self { view, buffer }
+    }
+
+    fn set_data(&self, data: &[u8]) {
+        let mut text = String::new();
+        for (i, chunk) in data
+            .chunks(Self::BYTES_PER_LINE)
+            .take(Self::MAX_LEN / Self::BYTES_PER_LINE)
+            .enumerate()
+        {
+            text += &format!("{:08x}  ", i * Self::BYTES_PER_LINE);
+            for j in 0..Self::BYTES_PER_LINE {
+                if j == Self::BYTES_PER_LINE / 2 {
+                    text.push(' ');
+                }
+                match chunk.get(j) {
+                    Some(byte) => text += &format!("{byte:02x} "),
+                    None => text += "   ",
+                }
+            }
+            text.push('|');
+            for &byte in chunk {
+                text.push(if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                });
+            }
+            text += "|\n";
+        }
+        if data.len() > Self::MAX_LEN {
+            text += &format!("... {} more bytes\n", data.len() - Self::MAX_LEN);
+        }
+
+        self.buffer.set_text(&text);
+    }
+
+    fn highlight(&self, start: usize, end: usize) {
+        let (buffer_start, buffer_end) = self.buffer.bounds();
+        self.buffer
+            .remove_tag_by_name(Self::HIGHLIGHT_TAG, &buffer_start, &buffer_end);
+
+        let end = end.min(Self::MAX_LEN);
+        if start >= end {
+            return;
+        }
+
+        let first_line = start / Self::BYTES_PER_LINE;
+        let last_line = (end - 1) / Self::BYTES_PER_LINE;
+        for line in first_line..=last_line {
+            let line_start = line * Self::BYTES_PER_LINE;
+            let first = start.max(line_start) - line_start;
+            let last = end.min(line_start + Self::BYTES_PER_LINE) - 1 - line_start;
+
+            let line = line as i32;
+            self.apply_highlight(line, Self::hex_column(first), Self::hex_column(last) + 2);
+            self.apply_highlight(
+                line,
+                Self::ascii_column(first),
+                Self::ascii_column(last) + 1,
+            );
+        }
+
+        let mut iter = self.buffer.iter_at_line(first_line as i32);
+        self.view.scroll_to_iter(&mut iter, 0.0, false, 0.0, 0.0);
+    }
+
+    fn apply_highlight(&self, line: i32, start: i32, end: i32) {
+        let start = self.buffer.iter_at_line_offset(line, start);
+        let end = self.buffer.iter_at_line_offset(line, end);
+        self.buffer
+            .apply_tag_by_name(Self::HIGHLIGHT_TAG, &start, &end);
+    }
+
+    fn hex_column(byte: usize) -> i32 {
+        (10 + byte * 3 + usize::from(byte >= Self::BYTES_PER_LINE / 2)) as i32
+    }
+
+    fn ascii_column(byte: usize) -> i32 {
+        (10 + Self::BYTES_PER_LINE * 3 + 2 + byte) as i32
+    }
+}
+
+fn add_text_column(tree_view: &gtk::TreeView, column: Column, title: &str) {
+    let renderer = gtk::CellRendererText::new();
+    let view_column = gtk::TreeViewColumn::new();
+    view_column.set_resizable(true);
+    view_column.set_title(title);
+    TreeViewColumnExt::pack_start(&view_column, &renderer, true);
+    TreeViewColumnExt::add_attribute(&view_column, &renderer, "text", column as i32);
+    tree_view.append_column(&view_column);
+}
+
+#[derive(Debug)]
+#[repr(i32)]
+enum Column {
+    Name,
+    Value,
+    Part,
+    Start,
+    End,
+}
@@ -57,6 +57,42 @@ pub fn folder_dialog<'a, P: IsA<gtk::Window> + 'a, Q: Into<Option<&'a P>>>(
     p
 }
 
+pub fn file_dialog<'a, P: IsA<gtk::Window> + 'a, Q: Into<Option<&'a P>>>(
+    parent: Q,
+    title: &str,
+    action: gtk::FileChooserAction,
+) -> gtk::FileChooserDialog {
+    let p = gtk::FileChooserDialog::new(Some(title), parent.into(), action);
+
+    p.set_destroy_with_parent(true);
+    p.set_do_overwrite_confirmation(true);
+    p.set_modal(true);
+
+    let accept = match action {
+        gtk::FileChooserAction::Save => "Save",
+        _ => "Open",
+    };
+    p.add_buttons(&[
+        (accept, gtk::ResponseType::Ok),
+        ("Cancel", gtk::ResponseType::Cancel),
+    ]);
+
+    p
+}
+
+pub fn confirm_dialog<'a, P: IsA<gtk::Window> + 'a, Q: Into<Option<&'a P>>>(
+    parent: Q,
+    msg: impl std::fmt::Display,
+) -> gtk::MessageDialog {
+    gtk::MessageDialog::new(
+        parent.into(),
+        gtk::DialogFlags::MODAL,
+        gtk::MessageType::Question,
+        gtk::ButtonsType::OkCancel,
+        &msg.to_string(),
+    )
+}
+
 pub fn error_dialog<'a, P: IsA<gtk::Window> + 'a, Q: Into<Option<&'a P>>>(
     parent: Q,
     msg: impl std::fmt::Display,
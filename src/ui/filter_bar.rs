@@ -0,0 +1,147 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::glib;
+use gtk::prelude::*;
+
+pub struct FilterBar {
+    container: gtk::Box,
+    entry: gtk::Entry,
+    error_label: gtk::Label,
+    presets_box: gtk::Box,
+    presets: Rc<RefCell<Vec<Preset>>>,
+}
+
+/// Toggle which fills in and applies a predefined expression.
+struct Preset {
+    button: gtk::ToggleButton,
+    expr: &'static str,
+}
+
+impl FilterBar {
+    pub fn new() -> Self {
+        let entry = gtk::Entry::new();
+        entry.set_placeholder_text(Some(
+            "e.g. key.workchain == -1 && key.seqno > 1000000, or len(value) > 65536",
+        ));
+
+        let error_label = gtk::Label::new(None);
+        error_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        error_label.set_no_show_all(true);
+
+        let presets_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        let presets = Rc::new(RefCell::new(Vec::<Preset>::new()));
+
+        // Keeps the toggles in sync with expressions typed by hand
+        entry.connect_activate({
+            let presets = presets.clone();
+            move |entry| {
+                let text = entry.text();
+                for preset in presets.borrow().iter() {
+                    preset.button.set_active(text.trim() == preset.expr);
+                }
+            }
+        });
+
+        let container = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        container.set_border_width(4);
+        container.pack_start(&gtk::Label::new(Some("Filter:")), false, false, 0);
+        container.pack_start(&entry, true, true, 0);
+        container.pack_start(&error_label, false, false, 0);
+        container.pack_start(&presets_box, false, false, 0);
+
+        Self {
+            container,
+            entry,
+            error_label,
+            presets_box,
+            presets,
+        }
+    }
+
+    /// Calls `f` with the expression when the entry is activated.
+    pub fn connect_apply<F>(&self, f: F)
+    where
+        F: Fn(&str) + 'static,
+    {
+        self.entry
+            .connect_activate(move |entry| f(entry.text().trim()));
+    }
+
+    /// Replaces the preset toggles, given as labels and expressions.
+    pub fn set_presets(&self, presets: &[(&'static str, &'static str)]) {
+        let unchanged = self
+            .presets
+            .borrow()
+            .iter()
+            .map(|preset| preset.expr)
+            .eq(presets.iter().map(|(_, expr)| *expr));
+        if unchanged {
+            return;
+        }
+
+        for preset in self.presets.borrow_mut().drain(..) {
+            self.presets_box.remove(&preset.button);
+        }
+
+        let text = self.entry.text();
+        let mut items = Vec::with_capacity(presets.len());
+        for &(label, expr) in presets {
+            let button = gtk::ToggleButton::with_label(label);
+            button.set_tooltip_text(Some(expr));
+            button.set_active(text.trim() == expr);
+            button.connect_toggled({
+                let entry = self.entry.clone();
+                let presets = self.presets.clone();
+                move |button| {
+                    let text = entry.text();
+                    if button.is_active() && text.trim() != expr {
+                        entry.set_text(expr);
+                        for preset in presets.borrow().iter() {
+                            if preset.expr != expr {
+                                preset.button.set_active(false);
+                            }
+                        }
+                        entry.activate();
+                    } else if !button.is_active() && text.trim() == expr {
+                        entry.set_text("");
+                        entry.activate();
+                    }
+                }
+            });
+
+            self.presets_box.pack_start(&button, false, false, 0);
+            button.show();
+            items.push(Preset { button, expr });
+        }
+        *self.presets.borrow_mut() = items;
+    }
+
+    pub fn clear(&self) {
+        self.entry.set_text("");
+        self.set_error(None);
+        for preset in self.presets.borrow().iter() {
+            preset.button.set_active(false);
+        }
+    }
+
+    pub fn set_error(&self, error: Option<&str>) {
+        match error {
+            Some(error) => {
+                self.error_label.set_markup(&format!(
+                    "<span foreground=\"red\">{}</span>",
+                    glib::markup_escape_text(error)
+                ));
+                self.error_label.set_tooltip_text(Some(error));
+                self.error_label.show();
+            }
+            None => self.error_label.hide(),
+        }
+    }
+}
+
+impl AsRef<gtk::Box> for FilterBar {
+    fn as_ref(&self) -> &gtk::Box {
+        &self.container
+    }
+}
@@ -0,0 +1,139 @@
+use gtk::glib;
+use gtk::prelude::*;
+
+/// Point lookup of one or many keys in the selected column family.
+pub struct GetDialog {
+    dialog: gtk::Dialog,
+    keys_buffer: gtk::TextBuffer,
+    store: gtk::ListStore,
+}
+
+impl GetDialog {
+    pub fn new<P: IsA<gtk::Window>>(parent: &P) -> Self {
+        let dialog = gtk::Dialog::new();
+        dialog.set_transient_for(Some(parent));
+        dialog.set_destroy_with_parent(true);
+        dialog.set_default_size(960, 600);
+        dialog.connect_delete_event(|p, _| p.hide_on_delete());
+
+        dialog.add_buttons(&[
+            ("Get", gtk::ResponseType::Apply),
+            ("Close", gtk::ResponseType::Close),
+        ]);
+
+        let keys_view = gtk::TextView::new();
+        keys_view.set_monospace(true);
+        let keys_buffer = keys_view.buffer().unwrap();
+
+        let keys_window = gtk::ScrolledWindow::new(gtk::Adjustment::NONE, gtk::Adjustment::NONE);
+        keys_window.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
+        keys_window.set_height_request(120);
+        keys_window.add(&keys_view);
+
+        let column_types = [glib::Type::STRING; 4];
+        let store = gtk::ListStore::new(&column_types);
+        let results_view = gtk::TreeView::with_model(&store);
+        add_text_column(&results_view, Column::Input, "Input");
+        add_text_column(&results_view, Column::Status, "Status");
+        add_text_column(&results_view, Column::Key, "Key");
+        add_text_column(&results_view, Column::Value, "Value");
+
+        let results_window = gtk::ScrolledWindow::new(gtk::Adjustment::NONE, gtk::Adjustment::NONE);
+        results_window.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
+        results_window.add(&results_view);
+
+        let hint = gtk::Label::new(Some(
+            "Keys, one per line: as displayed, 0x-prefixed hex or prefixed with a decoder id \
+             (e.g. base64:AAAA)",
+        ));
+        hint.set_halign(gtk::Align::Start);
+
+        let content = dialog.content_area();
+        content.set_spacing(4);
+        content.set_border_width(4);
+        content.pack_start(&hint, false, false, 0);
+        content.pack_start(&keys_window, false, true, 0);
+        content.pack_start(&results_window, true, true, 0);
+
+        Self {
+            dialog,
+            keys_buffer,
+            store,
+        }
+    }
+
+    pub fn present(&self, cf_name: &str) {
+        self.dialog.set_title(&format!("Get from {cf_name}"));
+        self.dialog.show_all();
+        self.dialog.present();
+    }
+
+    pub fn connect_get<F>(&self, f: F)
+    where
+        F: Fn(Vec<String>) + 'static,
+    {
+        let keys_buffer = self.keys_buffer.clone();
+        self.dialog.connect_response(move |dialog, response| {
+            if response != gtk::ResponseType::Apply {
+                dialog.hide();
+                return;
+            }
+
+            let (start, end) = keys_buffer.bounds();
+            let Some(text) = keys_buffer.text(&start, &end, false) else {
+                return;
+            };
+            let keys = text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(ToOwned::to_owned)
+                .collect();
+            f(keys);
+        });
+    }
+
+    pub fn set_results<I>(&self, results: I)
+    where
+        I: IntoIterator<Item = GetResult>,
+    {
+        self.store.clear();
+        for result in results {
+            self.store.set(
+                &self.store.append(),
+                &[
+                    (Column::Input as u32, &result.input),
+                    (Column::Status as u32, &result.status),
+                    (Column::Key as u32, &result.key),
+                    (Column::Value as u32, &result.value),
+                ],
+            );
+        }
+    }
+}
+
+pub struct GetResult {
+    pub input: String,
+    pub status: String,
+    pub key: String,
+    pub value: String,
+}
+
+fn add_text_column(tree_view: &gtk::TreeView, column: Column, title: &str) {
+    let renderer = gtk::CellRendererText::new();
+    let view_column = gtk::TreeViewColumn::new();
+    view_column.set_resizable(true);
+    view_column.set_title(title);
+    TreeViewColumnExt::pack_start(&view_column, &renderer, true);
+    TreeViewColumnExt::add_attribute(&view_column, &renderer, "text", column as i32);
+    tree_view.append_column(&view_column);
+}
+
+#[derive(Debug)]
+#[repr(i32)]
+enum Column {
+    Input,
+    Status,
+    Key,
+    Value,
+}
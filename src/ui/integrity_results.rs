@@ -0,0 +1,163 @@
+use gtk::glib;
+use gtk::prelude::*;
+
+use crate::controller::IntegrityIssue;
+
+/// Problems found by an integrity check.
+pub struct IntegrityResultsDialog {
+    dialog: gtk::Dialog,
+    tree_view: gtk::TreeView,
+    store: gtk::ListStore,
+    note_label: gtk::Label,
+    progress_bar: gtk::ProgressBar,
+    cancel_btn: gtk::Button,
+}
+
+impl IntegrityResultsDialog {
+    pub fn new<P: IsA<gtk::Window>>(parent: &P) -> Self {
+        let column_types = [
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Bytes::static_type(),
+        ];
+
+        let store = gtk::ListStore::new(&column_types);
+        let tree_view = gtk::TreeView::with_model(&store);
+        add_text_column(&tree_view, Column::CfName, "Column family");
+        add_text_column(&tree_view, Column::Entry, "Entry");
+        add_text_column(&tree_view, Column::Problem, "Problem");
+
+        let scrolled_window =
+            gtk::ScrolledWindow::new(gtk::Adjustment::NONE, gtk::Adjustment::NONE);
+        scrolled_window.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Always);
+        scrolled_window.add(&tree_view);
+
+        let note_label = gtk::Label::new(None);
+        note_label.set_xalign(0.0);
+        note_label.set_line_wrap(true);
+        note_label.set_margin_start(4);
+        note_label.set_no_show_all(true);
+
+        let progress_bar = gtk::ProgressBar::new();
+        progress_bar.set_show_text(true);
+        progress_bar.set_valign(gtk::Align::Center);
+
+        let cancel_btn = gtk::Button::with_label("Cancel");
+
+        let status_box = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        status_box.set_border_width(4);
+        status_box.pack_start(&progress_bar, true, true, 0);
+        status_box.pack_start(&cancel_btn, false, false, 0);
+
+        let dialog = gtk::Dialog::new();
+        dialog.set_transient_for(Some(parent));
+        dialog.set_destroy_with_parent(true);
+        dialog.set_default_size(960, 600);
+        dialog.connect_delete_event(|p, _| p.hide_on_delete());
+        dialog
+            .content_area()
+            .pack_start(&scrolled_window, true, true, 0);
+        dialog
+            .content_area()
+            .pack_start(&note_label, false, false, 0);
+        dialog
+            .content_area()
+            .pack_start(&status_box, false, false, 0);
+
+        Self {
+            dialog,
+            tree_view,
+            store,
+            note_label,
+            progress_bar,
+            cancel_btn,
+        }
+    }
+
+    pub fn set_title(&self, title: &str) {
+        self.dialog.set_title(title);
+    }
+
+    /// Shows what the check leaves out below the results.
+    pub fn set_note(&self, note: Option<&str>) {
+        self.note_label.set_text(note.unwrap_or_default());
+        self.note_label.set_visible(note.is_some());
+    }
+
+    pub fn present(&self) {
+        self.dialog.show_all();
+        self.dialog.present();
+    }
+
+    pub fn clear(&self) {
+        self.store.clear();
+    }
+
+    pub fn append(&self, issue: IntegrityIssue) {
+        self.store.insert_with_values(
+            None,
+            &[
+                (Column::CfName as u32, &issue.cf_name),
+                (Column::Entry as u32, &issue.entry),
+                (Column::Problem as u32, &issue.problem),
+                (Column::RawKey as u32, &glib::Bytes::from_owned(issue.key)),
+            ],
+        );
+    }
+
+    pub fn set_running(&self, running: bool) {
+        self.cancel_btn.set_sensitive(running);
+    }
+
+    pub fn set_progress(&self, fraction: f64, text: &str) {
+        self.progress_bar.set_fraction(fraction);
+        self.progress_bar.set_text(Some(text));
+    }
+
+    pub fn connect_cancel<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.cancel_btn.connect_clicked(move |_| f());
+    }
+
+    /// Calls `f` with the column family and raw key of an activated issue.
+    pub fn connect_issue_activated<F>(&self, f: F)
+    where
+        F: Fn(&str, &[u8]) + 'static,
+    {
+        let store = self.store.clone();
+        self.tree_view.connect_row_activated(move |_, path, _| {
+            let Some(iter) = store.iter(path) else {
+                return;
+            };
+
+            let cf_name = store.value(&iter, Column::CfName as i32);
+            let key = store.value(&iter, Column::RawKey as i32);
+            f(
+                cf_name.get::<&str>().unwrap(),
+                &key.get::<glib::Bytes>().unwrap(),
+            );
+        });
+    }
+}
+
+fn add_text_column(tree_view: &gtk::TreeView, column: Column, title: &str) {
+    let renderer = gtk::CellRendererText::new();
+    let view_column = gtk::TreeViewColumn::new();
+    view_column.set_resizable(true);
+    view_column.set_title(title);
+    TreeViewColumnExt::pack_start(&view_column, &renderer, true);
+    TreeViewColumnExt::add_attribute(&view_column, &renderer, "text", column as i32);
+    tree_view.append_column(&view_column);
+}
+
+#[derive(Debug)]
+#[repr(i32)]
+enum Column {
+    CfName,
+    Entry,
+    Problem,
+    RawKey,
+}
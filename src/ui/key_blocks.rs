@@ -0,0 +1,202 @@
+use std::cell::Cell;
+
+use gtk::glib;
+use gtk::prelude::*;
+
+use crate::controller::{ConfigChange, KeyBlock};
+
+/// Page listing key blocks in seqno order with the time between them and config changes.
+pub struct KeyBlocksView {
+    container: gtk::Box,
+    load_btn: gtk::Button,
+    cancel_btn: gtk::Button,
+    progress_bar: gtk::ProgressBar,
+    tree_view: gtk::TreeView,
+    store: gtk::ListStore,
+    last_gen_utime: Cell<Option<u32>>,
+}
+
+impl KeyBlocksView {
+    pub fn new() -> Self {
+        let load_btn = gtk::Button::with_label("Load key blocks");
+        let cancel_btn = gtk::Button::with_label("Cancel");
+        cancel_btn.set_sensitive(false);
+
+        let progress_bar = gtk::ProgressBar::new();
+        progress_bar.set_show_text(true);
+        progress_bar.set_valign(gtk::Align::Center);
+
+        let toolbar = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        toolbar.set_border_width(4);
+        toolbar.pack_start(&load_btn, false, false, 0);
+        toolbar.pack_start(&progress_bar, true, true, 0);
+        toolbar.pack_start(&cancel_btn, false, false, 0);
+
+        let column_types = [
+            glib::Type::U32,
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::STRING,
+        ];
+
+        let store = gtk::ListStore::new(&column_types);
+        let tree_view = gtk::TreeView::with_model(&store);
+        add_text_column(&tree_view, Column::Seqno, "Seqno");
+        add_text_column(&tree_view, Column::GenTime, "Generated");
+        add_text_column(&tree_view, Column::Interval, "Since previous");
+        add_text_column(&tree_view, Column::Config, "Config");
+        add_text_column(&tree_view, Column::BlockId, "Block id");
+
+        let scrolled_window =
+            gtk::ScrolledWindow::new(gtk::Adjustment::NONE, gtk::Adjustment::NONE);
+        scrolled_window.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
+        scrolled_window.add(&tree_view);
+
+        let container = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        container.pack_start(&toolbar, false, false, 0);
+        container.pack_start(&scrolled_window, true, true, 0);
+
+        Self {
+            container,
+            load_btn,
+            cancel_btn,
+            progress_bar,
+            tree_view,
+            store,
+            last_gen_utime: Cell::new(None),
+        }
+    }
+
+    pub fn connect_load<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.load_btn.connect_clicked(move |_| f());
+    }
+
+    pub fn connect_cancel<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.cancel_btn.connect_clicked(move |_| f());
+    }
+
+    /// Calls `f` with the block id of an activated key block.
+    pub fn connect_block_activated<F>(&self, f: F)
+    where
+        F: Fn(&str) + 'static,
+    {
+        let store = self.store.clone();
+        self.tree_view.connect_row_activated(move |_, path, _| {
+            let Some(iter) = store.iter(path) else {
+                return;
+            };
+
+            let block_id = store.value(&iter, Column::BlockId as i32);
+            let block_id = block_id.get::<&str>().unwrap();
+            if !block_id.is_empty() {
+                f(block_id);
+            }
+        });
+    }
+
+    pub fn clear(&self) {
+        self.store.clear();
+        self.last_gen_utime.set(None);
+        self.progress_bar.set_fraction(0.0);
+        self.progress_bar.set_text(Some(""));
+    }
+
+    pub fn append(&self, key_block: KeyBlock) {
+        let interval = match (self.last_gen_utime.get(), key_block.gen_utime) {
+            (Some(last), Some(gen_utime)) => format_interval(gen_utime.saturating_sub(last)),
+            _ => String::new(),
+        };
+        if key_block.gen_utime.is_some() {
+            self.last_gen_utime.set(key_block.gen_utime);
+        }
+
+        let (config, background) = match &key_block.config {
+            ConfigChange::Unknown => (String::new(), None),
+            ConfigChange::First => ("initial".to_owned(), None),
+            ConfigChange::Unchanged => ("unchanged".to_owned(), None),
+            ConfigChange::Changed(params) => {
+                let params = params.iter().map(u32::to_string).collect::<Vec<_>>();
+                (format!("changed: {}", params.join(", ")), Some("#fce8b2"))
+            }
+        };
+        let block_id = key_block.id.map(|id| id.to_string()).unwrap_or_default();
+
+        self.store.insert_with_values(
+            None,
+            &[
+                (Column::Seqno as u32, &key_block.seqno),
+                (
+                    Column::GenTime as u32,
+                    &key_block.gen_time().unwrap_or_default(),
+                ),
+                (Column::Interval as u32, &interval),
+                (Column::Config as u32, &config),
+                (Column::BlockId as u32, &block_id),
+                (Column::Background as u32, &background),
+            ],
+        );
+    }
+
+    pub fn set_running(&self, running: bool) {
+        self.load_btn.set_sensitive(!running);
+        self.cancel_btn.set_sensitive(running);
+    }
+
+    pub fn set_progress(&self, fraction: f64, text: &str) {
+        self.progress_bar.set_fraction(fraction);
+        self.progress_bar.set_text(Some(text));
+    }
+}
+
+impl AsRef<gtk::Box> for KeyBlocksView {
+    fn as_ref(&self) -> &gtk::Box {
+        &self.container
+    }
+}
+
+fn format_interval(secs: u32) -> String {
+    let (days, hours, minutes) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m {}s", secs % 60)
+    }
+}
+
+fn add_text_column(tree_view: &gtk::TreeView, column: Column, title: &str) {
+    let renderer = gtk::CellRendererText::new();
+    let view_column = gtk::TreeViewColumn::new();
+    view_column.set_resizable(true);
+    view_column.set_title(title);
+    TreeViewColumnExt::pack_start(&view_column, &renderer, true);
+    TreeViewColumnExt::add_attribute(&view_column, &renderer, "text", column as i32);
+    // Config changes are highlighted
+    TreeViewColumnExt::add_attribute(
+        &view_column,
+        &renderer,
+        "cell-background",
+        Column::Background as i32,
+    );
+    tree_view.append_column(&view_column);
+}
+
+#[derive(Debug)]
+#[repr(i32)]
+enum Column {
+    Seqno,
+    GenTime,
+    Interval,
+    Config,
+    BlockId,
+    Background,
+}
@@ -6,6 +6,14 @@ pub struct MenuBar {
     pub open: gtk::MenuItem,
     pub about: gtk::MenuItem,
     pub exit: gtk::MenuItem,
+
+    pub node_state: gtk::MenuItem,
+    pub block_explorer: gtk::MenuItem,
+    pub chain_graph: gtk::MenuItem,
+    pub archive_coverage: gtk::MenuItem,
+    pub key_blocks: gtk::MenuItem,
+    pub verify_hashes: gtk::MenuItem,
+    pub check_chain_links: gtk::MenuItem,
 }
 
 impl MenuBar {
@@ -26,11 +34,39 @@ impl MenuBar {
         file.set_submenu(Some(&file_menu));
         menu_bar.add(&file);
 
+        let tools = gtk::MenuItem::with_label("Tools");
+        let tools_menu = gtk::Menu::new();
+        let tools_menu_node_state = gtk::MenuItem::with_label("Node state");
+        let tools_menu_block_explorer = gtk::MenuItem::with_label("Block explorer");
+        let tools_menu_chain_graph = gtk::MenuItem::with_label("Chain graph");
+        let tools_menu_archive_coverage = gtk::MenuItem::with_label("Archive coverage");
+        let tools_menu_key_blocks = gtk::MenuItem::with_label("Key blocks");
+        let tools_menu_verify_hashes = gtk::MenuItem::with_label("Verify hashes…");
+        let tools_menu_check_chain_links = gtk::MenuItem::with_label("Check chain links…");
+
+        tools_menu.add(&tools_menu_node_state);
+        tools_menu.add(&tools_menu_block_explorer);
+        tools_menu.add(&tools_menu_chain_graph);
+        tools_menu.add(&tools_menu_archive_coverage);
+        tools_menu.add(&tools_menu_key_blocks);
+        tools_menu.add(&gtk::SeparatorMenuItem::new());
+        tools_menu.add(&tools_menu_verify_hashes);
+        tools_menu.add(&tools_menu_check_chain_links);
+        tools.set_submenu(Some(&tools_menu));
+        menu_bar.add(&tools);
+
         Self {
             menu_bar,
             open: file_menu_open,
             about: file_menu_about,
             exit: file_menu_exit,
+            node_state: tools_menu_node_state,
+            block_explorer: tools_menu_block_explorer,
+            chain_graph: tools_menu_chain_graph,
+            archive_coverage: tools_menu_archive_coverage,
+            key_blocks: tools_menu_key_blocks,
+            verify_hashes: tools_menu_verify_hashes,
+            check_chain_links: tools_menu_check_chain_links,
         }
     }
 }
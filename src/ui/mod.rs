@@ -1,9 +1,39 @@
+pub use archive_coverage::*;
+pub use block_explorer::*;
+pub use block_navigator::*;
+pub use cell_tree::*;
 pub use cf_list::*;
+pub use cf_toolbar::*;
 pub use cf_view::*;
+pub use chain_graph::*;
+pub use clipboard::*;
+pub use detail_view::*;
 pub use dialogs::*;
+pub use filter_bar::*;
+pub use get_dialog::*;
+pub use integrity_results::*;
+pub use key_blocks::*;
 pub use menu_bar::*;
+pub use node_state::*;
+pub use search_bar::*;
+pub use search_results::*;
 
+mod archive_coverage;
+mod block_explorer;
+mod block_navigator;
+mod cell_tree;
 mod cf_list;
+mod cf_toolbar;
 mod cf_view;
+mod chain_graph;
+mod clipboard;
+mod detail_view;
 mod dialogs;
+mod filter_bar;
+mod get_dialog;
+mod integrity_results;
+mod key_blocks;
 mod menu_bar;
+mod node_state;
+mod search_bar;
+mod search_results;
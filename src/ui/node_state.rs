@@ -0,0 +1,217 @@
+use std::rc::Rc;
+
+use gtk::glib;
+use gtk::prelude::*;
+
+use crate::controller::{BlockIdFull, NodeState};
+
+/// Dashboard with the sync progress of a ton-indexer node.
+pub struct NodeStateView {
+    container: gtk::Box,
+    refresh_btn: gtk::Button,
+    db_version: gtk::Label,
+    init_mc_block: gtk::Label,
+    shards_client_mc_block: gtk::Label,
+    last_mc_block: gtk::Label,
+    sync_progress: gtk::ProgressBar,
+    background_sync: gtk::Label,
+    last_uploaded_archive: gtk::Label,
+    last_key_block: gtk::Label,
+    last_mc_state: gtk::Label,
+}
+
+impl NodeStateView {
+    pub fn new() -> Self {
+        let refresh_btn = gtk::Button::with_label("Refresh");
+        refresh_btn.set_halign(gtk::Align::Start);
+
+        let grid = gtk::Grid::new();
+        grid.set_row_spacing(8);
+        grid.set_column_spacing(16);
+
+        let mut row = 0;
+        let mut add_row = |title: &str, widget: &gtk::Widget| {
+            let title = gtk::Label::new(Some(title));
+            title.set_xalign(1.0);
+            title.style_context().add_class("dim-label");
+            grid.attach(&title, 0, row, 1, 1);
+            grid.attach(widget, 1, row, 1, 1);
+            row += 1;
+        };
+
+        let db_version = value_label();
+        add_row("DB version", db_version.upcast_ref());
+        let init_mc_block = value_label();
+        add_row("Init MC block", init_mc_block.upcast_ref());
+        let shards_client_mc_block = value_label();
+        add_row(
+            "Shards client MC block",
+            shards_client_mc_block.upcast_ref(),
+        );
+        let last_mc_block = value_label();
+        add_row("Last MC block", last_mc_block.upcast_ref());
+
+        let sync_progress = gtk::ProgressBar::new();
+        sync_progress.set_show_text(true);
+        sync_progress.set_hexpand(true);
+        add_row("Sync progress", sync_progress.upcast_ref());
+
+        let background_sync = value_label();
+        add_row("Background sync", background_sync.upcast_ref());
+        let last_uploaded_archive = value_label();
+        add_row("Last uploaded archive", last_uploaded_archive.upcast_ref());
+        let last_key_block = value_label();
+        add_row("Newest key block", last_key_block.upcast_ref());
+        let last_mc_state = value_label();
+        add_row("Newest MC state", last_mc_state.upcast_ref());
+
+        let container = gtk::Box::new(gtk::Orientation::Vertical, 12);
+        container.set_border_width(12);
+        container.pack_start(&refresh_btn, false, false, 0);
+        container.pack_start(&grid, false, false, 0);
+
+        Self {
+            container,
+            refresh_btn,
+            db_version,
+            init_mc_block,
+            shards_client_mc_block,
+            last_mc_block,
+            sync_progress,
+            background_sync,
+            last_uploaded_archive,
+            last_key_block,
+            last_mc_state,
+        }
+    }
+
+    pub fn connect_refresh<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.refresh_btn.connect_clicked(move |_| f());
+    }
+
+    /// Calls `f` with the full id of a clicked block.
+    pub fn connect_block_activated<F>(&self, f: F)
+    where
+        F: Fn(&str) + 'static,
+    {
+        let f = Rc::new(f);
+        for label in [
+            &self.init_mc_block,
+            &self.shards_client_mc_block,
+            &self.last_mc_block,
+            &self.background_sync,
+        ] {
+            let f = f.clone();
+            label.connect_activate_link(move |_, uri| {
+                f(uri);
+                Inhibit(true)
+            });
+        }
+    }
+
+    pub fn show(&self, state: &NodeState) {
+        let db_version = state
+            .db_version
+            .map(|[major, minor, patch]| format!("{major}.{minor}.{patch}"));
+        self.db_version.set_text(&db_version.unwrap_or_default());
+
+        self.init_mc_block
+            .set_markup(&block_markup(state.init_mc_block.as_ref()));
+        self.shards_client_mc_block
+            .set_markup(&block_markup(state.shards_client_mc_block.as_ref()));
+        self.last_mc_block
+            .set_markup(&block_markup(state.last_mc_block.as_ref()));
+
+        let seqno = |id: &Option<BlockIdFull>| id.as_ref().map(|id| id.seqno);
+        match (
+            seqno(&state.init_mc_block),
+            seqno(&state.shards_client_mc_block),
+            seqno(&state.last_mc_block),
+        ) {
+            (Some(init), Some(shards_client), Some(last)) => {
+                let total = last.saturating_sub(init);
+                let done = shards_client.saturating_sub(init).min(total);
+                let fraction = if total == 0 {
+                    1.0
+                } else {
+                    done as f64 / total as f64
+                };
+                self.sync_progress.set_fraction(fraction);
+                self.sync_progress.set_text(Some(&format!(
+                    "shards client {done} of {total} blocks since init, {} behind",
+                    last.saturating_sub(shards_client)
+                )));
+            }
+            _ => {
+                self.sync_progress.set_fraction(0.0);
+                self.sync_progress.set_text(Some("unknown"));
+            }
+        }
+
+        let background_sync = match (&state.background_sync_low, &state.background_sync_high) {
+            (Some(low), Some(high)) => format!(
+                "{} – {}, {} blocks",
+                block_markup(Some(low)),
+                block_markup(Some(high)),
+                high.seqno.saturating_sub(low.seqno)
+            ),
+            (low, high) => format!(
+                "{} – {}",
+                block_markup(low.as_ref()),
+                block_markup(high.as_ref())
+            ),
+        };
+        self.background_sync.set_markup(&background_sync);
+
+        let archive = state.last_uploaded_archive.map(|id| id.to_string());
+        self.last_uploaded_archive
+            .set_text(&archive.unwrap_or_default());
+
+        let last = seqno(&state.last_mc_block);
+        self.last_key_block
+            .set_text(&seqno_with_lag(state.last_key_block_seqno, last));
+        self.last_mc_state
+            .set_text(&seqno_with_lag(state.last_mc_state_seqno, last));
+    }
+}
+
+impl AsRef<gtk::Box> for NodeStateView {
+    fn as_ref(&self) -> &gtk::Box {
+        &self.container
+    }
+}
+
+fn value_label() -> gtk::Label {
+    let label = gtk::Label::new(None);
+    label.set_xalign(0.0);
+    label.set_selectable(true);
+    label
+}
+
+/// Short id linking to the full one, or "none".
+fn block_markup(id: Option<&BlockIdFull>) -> String {
+    match id {
+        Some(id) => format!(
+            "<a href=\"{}\">{}</a>",
+            glib::markup_escape_text(&id.to_string()),
+            glib::markup_escape_text(&id.short().to_string())
+        ),
+        None => "none".to_owned(),
+    }
+}
+
+fn seqno_with_lag(seqno: Option<u32>, last_mc_seqno: Option<u32>) -> String {
+    match (seqno, last_mc_seqno) {
+        (Some(seqno), Some(last)) if seqno <= last => {
+            format!("{seqno}, {} blocks before the last MC block", last - seqno)
+        }
+        (Some(seqno), Some(last)) => {
+            format!("{seqno}, {} blocks after the last MC block", seqno - last)
+        }
+        (Some(seqno), None) => seqno.to_string(),
+        (None, _) => "none".to_owned(),
+    }
+}
@@ -0,0 +1,122 @@
+use std::rc::Rc;
+
+use gtk::prelude::*;
+
+use crate::controller::{SearchMode, SearchTarget};
+
+pub struct SearchBar {
+    container: gtk::Box,
+    pattern_entry: gtk::Entry,
+    mode_combo: gtk::ComboBoxText,
+    target_combo: gtk::ComboBoxText,
+    all_cfs_check: gtk::CheckButton,
+    search_btn: gtk::Button,
+    cancel_btn: gtk::Button,
+    progress_bar: gtk::ProgressBar,
+}
+
+impl SearchBar {
+    pub fn new() -> Self {
+        let pattern_entry = gtk::Entry::new();
+        pattern_entry.set_placeholder_text(Some("Text, regex or hex bytes with ?? wildcards"));
+
+        let mode_combo = gtk::ComboBoxText::new();
+        for mode in SearchMode::ALL {
+            mode_combo.append_text(mode.name());
+        }
+        mode_combo.set_active(Some(0));
+
+        let target_combo = gtk::ComboBoxText::new();
+        for target in SearchTarget::ALL {
+            target_combo.append_text(target.name());
+        }
+        target_combo.set_active(Some(SearchTarget::Decoded as u32));
+
+        let all_cfs_check = gtk::CheckButton::with_label("All column families");
+
+        let search_btn = gtk::Button::with_label("Search");
+        let cancel_btn = gtk::Button::with_label("Cancel");
+        cancel_btn.set_sensitive(false);
+
+        let progress_bar = gtk::ProgressBar::new();
+        progress_bar.set_show_text(true);
+        progress_bar.set_text(Some(""));
+        progress_bar.set_valign(gtk::Align::Center);
+        progress_bar.set_width_request(200);
+
+        let container = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        container.set_border_width(4);
+        container.pack_start(&gtk::Label::new(Some("Search:")), false, false, 0);
+        container.pack_start(&pattern_entry, true, true, 0);
+        container.pack_start(&mode_combo, false, false, 0);
+        container.pack_start(&gtk::Label::new(Some("in")), false, false, 0);
+        container.pack_start(&target_combo, false, false, 0);
+        container.pack_start(&all_cfs_check, false, false, 0);
+        container.pack_start(&search_btn, false, false, 0);
+        container.pack_start(&cancel_btn, false, false, 0);
+        container.pack_start(&progress_bar, false, false, 0);
+
+        Self {
+            container,
+            pattern_entry,
+            mode_combo,
+            target_combo,
+            all_cfs_check,
+            search_btn,
+            cancel_btn,
+            progress_bar,
+        }
+    }
+
+    pub fn connect_search<F>(&self, f: F)
+    where
+        F: Fn(SearchMode, SearchTarget, &str, bool) + 'static,
+    {
+        let emit = {
+            let pattern_entry = self.pattern_entry.clone();
+            let mode_combo = self.mode_combo.clone();
+            let target_combo = self.target_combo.clone();
+            let all_cfs_check = self.all_cfs_check.clone();
+            move || {
+                let pattern = pattern_entry.text();
+                if pattern.is_empty() {
+                    return;
+                }
+
+                let mode = SearchMode::ALL[mode_combo.active().unwrap_or_default() as usize];
+                let target = SearchTarget::ALL[target_combo.active().unwrap_or_default() as usize];
+                f(mode, target, &pattern, all_cfs_check.is_active());
+            }
+        };
+
+        let emit = Rc::new(emit);
+        self.pattern_entry.connect_activate({
+            let emit = emit.clone();
+            move |_| emit()
+        });
+        self.search_btn.connect_clicked(move |_| emit());
+    }
+
+    pub fn connect_cancel<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.cancel_btn.connect_clicked(move |_| f());
+    }
+
+    pub fn set_running(&self, running: bool) {
+        self.search_btn.set_sensitive(!running);
+        self.cancel_btn.set_sensitive(running);
+    }
+
+    pub fn set_progress(&self, fraction: f64, text: &str) {
+        self.progress_bar.set_fraction(fraction);
+        self.progress_bar.set_text(Some(text));
+    }
+}
+
+impl AsRef<gtk::Box> for SearchBar {
+    fn as_ref(&self) -> &gtk::Box {
+        &self.container
+    }
+}
@@ -0,0 +1,147 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use gtk::glib;
+use gtk::prelude::*;
+
+use crate::controller::CfEntry;
+
+/// Matches of a search over all column families, grouped by column family.
+pub struct SearchResultsDialog {
+    dialog: gtk::Dialog,
+    tree_view: gtk::TreeView,
+    store: gtk::TreeStore,
+    groups: RefCell<HashMap<String, gtk::TreeIter>>,
+}
+
+impl SearchResultsDialog {
+    pub fn new<P: IsA<gtk::Window>>(parent: &P) -> Self {
+        let column_types = [
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Type::STRING,
+            glib::Bytes::static_type(),
+        ];
+
+        let store = gtk::TreeStore::new(&column_types);
+        let tree_view = gtk::TreeView::with_model(&store);
+        add_text_column(&tree_view, Column::Key, "Key");
+        add_text_column(&tree_view, Column::Value, "Value");
+
+        let scrolled_window =
+            gtk::ScrolledWindow::new(gtk::Adjustment::NONE, gtk::Adjustment::NONE);
+        scrolled_window.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Always);
+        scrolled_window.add(&tree_view);
+
+        let dialog = gtk::Dialog::new();
+        dialog.set_title("Search results");
+        dialog.set_transient_for(Some(parent));
+        dialog.set_destroy_with_parent(true);
+        dialog.set_default_size(960, 600);
+        dialog.connect_delete_event(|p, _| p.hide_on_delete());
+        dialog
+            .content_area()
+            .pack_start(&scrolled_window, true, true, 0);
+
+        Self {
+            dialog,
+            tree_view,
+            store,
+            groups: Default::default(),
+        }
+    }
+
+    pub fn present(&self) {
+        self.dialog.show_all();
+        self.dialog.present();
+    }
+
+    pub fn clear(&self) {
+        self.store.clear();
+        self.groups.borrow_mut().clear();
+    }
+
+    pub fn append(&self, cf_name: &str, entry: CfEntry) {
+        let mut groups = self.groups.borrow_mut();
+        let group = groups.entry(cf_name.to_owned()).or_insert_with(|| {
+            self.store.insert_with_values(
+                None,
+                None,
+                &[
+                    (Column::Key as u32, &cf_name),
+                    (Column::CfName as u32, &cf_name),
+                ],
+            )
+        });
+
+        self.store.insert_with_values(
+            Some(group),
+            None,
+            &[
+                (Column::Key as u32, &entry.decoded_key.to_string()),
+                (Column::Value as u32, &entry.decoded_value.to_string()),
+                (Column::CfName as u32, &cf_name),
+                (Column::RawKey as u32, &glib::Bytes::from_owned(entry.key)),
+            ],
+        );
+
+        let matches = self.store.iter_n_children(Some(group));
+        self.store.set_value(
+            group,
+            Column::Key as u32,
+            &format!("{cf_name} ({matches})").to_value(),
+        );
+        if matches == 1 {
+            if let Some(path) = self.store.path(group) {
+                self.tree_view.expand_row(&path, false);
+            }
+        }
+    }
+
+    /// Calls `f` with the column family and raw key of an activated match.
+    pub fn connect_hit_activated<F>(&self, f: F)
+    where
+        F: Fn(&str, &[u8]) + 'static,
+    {
+        self.tree_view
+            .connect_row_activated(move |tree_view, path, _| {
+                let Some(model) = tree_view.model() else {
+                    return;
+                };
+                let Some(iter) = model.iter(path) else {
+                    return;
+                };
+
+                let cf_name = model.value(&iter, Column::CfName as i32);
+                let key = model.value(&iter, Column::RawKey as i32);
+                match key.get::<Option<glib::Bytes>>().unwrap() {
+                    Some(key) => f(cf_name.get::<&str>().unwrap(), &key),
+                    None if tree_view.row_expanded(path) => {
+                        tree_view.collapse_row(path);
+                    }
+                    None => {
+                        tree_view.expand_row(path, false);
+                    }
+                }
+            });
+    }
+}
+
+fn add_text_column(tree_view: &gtk::TreeView, column: Column, title: &str) {
+    let renderer = gtk::CellRendererText::new();
+    let view_column = gtk::TreeViewColumn::new();
+    view_column.set_resizable(true);
+    view_column.set_title(title);
+    TreeViewColumnExt::pack_start(&view_column, &renderer, true);
+    TreeViewColumnExt::add_attribute(&view_column, &renderer, "text", column as i32);
+    tree_view.append_column(&view_column);
+}
+
+#[derive(Debug)]
+#[repr(i32)]
+enum Column {
+    Key,
+    Value,
+    CfName,
+    RawKey,
+}
@@ -1,12 +1,16 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use gtk::{gdk, gio, glib, prelude::*};
 use gtk::{Application, ApplicationWindow};
 
 use crate::controller;
+use crate::settings::DecoderSettings;
 use crate::ui;
 
 pub struct Window(Rc<WindowState>);
@@ -36,10 +40,27 @@ impl Window {
         window.add(&main_box);
 
         let folder_dialog = ui::folder_dialog(&window);
+        let get_dialog = ui::GetDialog::new(&window);
+        let search_results = ui::SearchResultsDialog::new(&window);
+        let integrity_results = ui::IntegrityResultsDialog::new(&window);
 
         let shared_state = Rc::new(WindowState {
             window,
             db: Default::default(),
+            selected_cf: Default::default(),
+            decoder_settings: RefCell::new(DecoderSettings::load()),
+            get_dialog,
+            search_results,
+            integrity_results,
+            search: Default::default(),
+            verify: Default::default(),
+            coverage: Default::default(),
+            key_blocks: Default::default(),
+            transfer: Default::default(),
+            explore: Default::default(),
+            chain_graph: Default::default(),
+            history: Default::default(),
+            filter: Default::default(),
             view_stack,
             welcome_page_view,
             db_page_view,
@@ -63,6 +84,42 @@ impl Window {
             }),
         );
 
+        menu_bar
+            .node_state
+            .connect_activate(glib::clone!(@weak shared_state => move |_| {
+                shared_state.show_node_state();
+            }));
+        menu_bar
+            .block_explorer
+            .connect_activate(glib::clone!(@weak shared_state => move |_| {
+                shared_state.show_block_explorer();
+            }));
+        menu_bar
+            .chain_graph
+            .connect_activate(glib::clone!(@weak shared_state => move |_| {
+                shared_state.show_chain_graph_page();
+            }));
+        menu_bar
+            .archive_coverage
+            .connect_activate(glib::clone!(@weak shared_state => move |_| {
+                shared_state.show_archive_coverage();
+            }));
+        menu_bar
+            .key_blocks
+            .connect_activate(glib::clone!(@weak shared_state => move |_| {
+                shared_state.show_key_blocks();
+            }));
+        menu_bar
+            .verify_hashes
+            .connect_activate(glib::clone!(@weak shared_state => move |_| {
+                shared_state.start_verify(controller::IntegrityCheck::Hashes);
+            }));
+        menu_bar
+            .check_chain_links
+            .connect_activate(glib::clone!(@weak shared_state => move |_| {
+                shared_state.start_verify(controller::IntegrityCheck::ChainLinks);
+            }));
+
         folder_dialog.connect_response(
             glib::clone!(@strong shared_state => move |file_chooser, response| {
                 if response == gtk::ResponseType::Ok {
@@ -70,85 +127,1571 @@ impl Window {
                         shared_state.open_db(path)
                     }
                 }
-                file_chooser.hide();
-            }),
-        );
+                file_chooser.hide();
+            }),
+        );
+
+        shared_state.welcome_page_view.open_btn.connect_clicked(
+            glib::clone!(@strong folder_dialog => move |_| {
+                folder_dialog.show_all();
+            }),
+        );
+
+        shared_state.db_page_view.cf_list.connect_cf_selected(
+            glib::clone!(@weak shared_state => move |cf_name| {
+                shared_state.push_history();
+                shared_state.open_cf(cf_name);
+            }),
+        );
+
+        shared_state
+            .db_page_view
+            .cf_toolbar
+            .back_btn
+            .connect_clicked(glib::clone!(@weak shared_state => move |_| {
+                shared_state.go_back();
+            }));
+
+        shared_state
+            .db_page_view
+            .cf_toolbar
+            .forward_btn
+            .connect_clicked(glib::clone!(@weak shared_state => move |_| {
+                shared_state.go_forward();
+            }));
+
+        shared_state.db_page_view.cf_toolbar.connect_seek(
+            glib::clone!(@weak shared_state => move |text| {
+                shared_state.seek_cf(text);
+            }),
+        );
+
+        shared_state
+            .db_page_view
+            .cf_toolbar
+            .get_btn
+            .connect_clicked(glib::clone!(@weak shared_state => move |_| {
+                let selected_cf = shared_state.selected_cf.borrow();
+                if let Some(cf_name) = &*selected_cf {
+                    shared_state.get_dialog.present(cf_name);
+                }
+            }));
+
+        shared_state
+            .get_dialog
+            .connect_get(glib::clone!(@weak shared_state => move |keys| {
+                shared_state.multi_get(&keys);
+            }));
+
+        shared_state.db_page_view.block_navigator.connect_navigate(
+            glib::clone!(@weak shared_state => move |workchain, shard, from, to| {
+                shared_state.navigate_blocks(workchain, shard, from, to);
+            }),
+        );
+
+        shared_state.db_page_view.filter_bar.connect_apply(
+            glib::clone!(@weak shared_state => move |text| {
+                shared_state.apply_filter(text);
+            }),
+        );
+
+        shared_state.db_page_view.search_bar.connect_search(
+            glib::clone!(@weak shared_state => move |mode, target, pattern, all_cfs| {
+                shared_state.start_search(mode, target, pattern, all_cfs);
+            }),
+        );
+
+        shared_state.db_page_view.search_bar.connect_cancel(
+            glib::clone!(@weak shared_state => move || {
+                shared_state.cancel_search();
+                shared_state.db_page_view.set_status_bar_text("Search cancelled");
+            }),
+        );
+
+        shared_state.search_results.connect_hit_activated(
+            glib::clone!(@weak shared_state => move |cf_name, key| {
+                shared_state.open_search_hit(cf_name, key);
+            }),
+        );
+
+        shared_state
+            .integrity_results
+            .connect_cancel(glib::clone!(@weak shared_state => move || {
+                shared_state.cancel_verify();
+            }));
+
+        shared_state.db_page_view.archive_coverage.connect_scan(
+            glib::clone!(@weak shared_state => move || {
+                shared_state.start_coverage();
+            }),
+        );
+
+        shared_state.db_page_view.archive_coverage.connect_cancel(
+            glib::clone!(@weak shared_state => move || {
+                shared_state.cancel_coverage();
+            }),
+        );
+
+        shared_state
+            .db_page_view
+            .archive_coverage
+            .connect_archive_activated(glib::clone!(@weak shared_state => move |key| {
+                shared_state.open_search_hit("archives", key);
+            }));
+
+        shared_state.db_page_view.node_state.connect_refresh(
+            glib::clone!(@weak shared_state => move || {
+                shared_state.show_node_state();
+            }),
+        );
+
+        shared_state
+            .db_page_view
+            .node_state
+            .connect_block_activated(glib::clone!(@weak shared_state => move |block_id| {
+                shared_state.explore_block(block_id);
+            }));
+
+        shared_state.db_page_view.key_blocks.connect_load(
+            glib::clone!(@weak shared_state => move || {
+                shared_state.start_key_blocks();
+            }),
+        );
+
+        shared_state.db_page_view.key_blocks.connect_cancel(
+            glib::clone!(@weak shared_state => move || {
+                shared_state.cancel_key_blocks();
+            }),
+        );
+
+        shared_state
+            .db_page_view
+            .key_blocks
+            .connect_block_activated(glib::clone!(@weak shared_state => move |block_id| {
+                shared_state.explore_block(block_id);
+            }));
+
+        shared_state.integrity_results.connect_issue_activated(
+            glib::clone!(@weak shared_state => move |cf_name, key| {
+                shared_state.open_search_hit(cf_name, key);
+            }),
+        );
+
+        shared_state.db_page_view.block_explorer.connect_explore(
+            glib::clone!(@weak shared_state => move |text| {
+                shared_state.explore_block(text);
+            }),
+        );
+
+        shared_state.db_page_view.block_explorer.connect_show_graph(
+            glib::clone!(@weak shared_state => move |text| {
+                let depth = shared_state.db_page_view.chain_graph.depth();
+                shared_state.show_chain_graph(text, depth);
+            }),
+        );
+
+        shared_state.db_page_view.chain_graph.connect_show(
+            glib::clone!(@weak shared_state => move |text, depth| {
+                shared_state.show_chain_graph(text, depth);
+            }),
+        );
+
+        shared_state
+            .db_page_view
+            .chain_graph
+            .connect_node_activated(glib::clone!(@weak shared_state => move |id| {
+                shared_state.explore_block(&id.to_string());
+            }));
+
+        shared_state
+            .db_page_view
+            .block_explorer
+            .connect_item_activated(glib::clone!(@weak shared_state => move |cf_name, key| {
+                shared_state.open_search_hit(cf_name, key);
+            }));
+
+        shared_state.db_page_view.cf_view.connect_row_selected(
+            glib::clone!(@weak shared_state => move |key| {
+                shared_state.show_entry_details(key);
+            }),
+        );
+
+        shared_state.db_page_view.cf_view.connect_link_activated(
+            glib::clone!(@weak shared_state => move |block_id| {
+                shared_state.follow_link(&block_id);
+            }),
+        );
+
+        shared_state.db_page_view.cf_view.connect_copy(
+            glib::clone!(@weak shared_state => move |content| {
+                shared_state.copy_selection(content);
+            }),
+        );
+
+        shared_state
+            .db_page_view
+            .cf_view
+            .save_value_item
+            .connect_activate(glib::clone!(@weak shared_state => move |_| {
+                shared_state.save_value();
+            }));
+
+        shared_state
+            .db_page_view
+            .cf_view
+            .replace_value_item
+            .connect_activate(glib::clone!(@weak shared_state => move |_| {
+                shared_state.replace_value();
+            }));
+
+        shared_state
+            .db_page_view
+            .cf_view
+            .explore_block_item
+            .connect_activate(glib::clone!(@weak shared_state => move |_| {
+                shared_state.explore_selected_block();
+            }));
+
+        shared_state.db_page_view.detail_view.connect_save_bytes(
+            glib::clone!(@weak shared_state => move |name, data| {
+                shared_state.save_bytes(name, data);
+            }),
+        );
+
+        shared_state.db_page_view.cf_view.connect_repr_selected(
+            glib::clone!(@weak shared_state => move |column, repr| {
+                shared_state.set_cf_repr(column, repr);
+            }),
+        );
+
+        shared_state.window.show_all();
+
+        if let Some(path) = initial_path {
+            shared_state.open_db(path);
+        }
+        Self(shared_state)
+    }
+}
+
+impl AsRef<ApplicationWindow> for Window {
+    fn as_ref(&self) -> &ApplicationWindow {
+        &self.0.window
+    }
+}
+
+struct WindowState {
+    window: ApplicationWindow,
+    db: RefCell<Option<controller::Db>>,
+    selected_cf: RefCell<Option<String>>,
+    decoder_settings: RefCell<DecoderSettings>,
+    get_dialog: ui::GetDialog,
+    search_results: ui::SearchResultsDialog,
+    integrity_results: ui::IntegrityResultsDialog,
+    search: RefCell<Option<RunningSearch>>,
+    verify: RefCell<Option<controller::Task>>,
+    coverage: RefCell<Option<controller::Task>>,
+    key_blocks: RefCell<Option<controller::Task>>,
+    transfer: RefCell<Option<controller::Task>>,
+    explore: RefCell<Option<controller::Task>>,
+    chain_graph: RefCell<Option<controller::Task>>,
+    history: RefCell<History>,
+    filter: RefCell<Option<Arc<controller::Filter>>>,
+    view_stack: gtk::Stack,
+    welcome_page_view: WelcomePageView,
+    db_page_view: DbPageView,
+}
+
+impl WindowState {
+    fn open_db(&self, path: PathBuf) {
+        let opened_db = match controller::Db::open(path) {
+            Ok(db) => db,
+            Err(e) => {
+                ui::error_dialog(&self.window, format!("{e:?}")).show_all();
+                return;
+            }
+        };
+
+        self.cancel_search();
+        self.cancel_verify();
+        self.cancel_coverage();
+        self.cancel_key_blocks();
+        self.cancel_transfer();
+        self.cancel_explore();
+        self.cancel_chain_graph();
+        self.history.take();
+        self.update_history_buttons();
+
+        // The column families of the previous DB may not exist in this one
+        self.selected_cf.take();
+        self.filter.take();
+        self.db_page_view.filter_bar.clear();
+
+        self.db_page_view
+            .init_for_db(self.db.borrow_mut().insert(opened_db));
+        self.select_page(&self.db_page_view);
+
+        // ton-indexer DBs open on the dashboard
+        self.show_node_state();
+    }
+
+    /// Reads the node state again and shows the dashboard, if the DB has one.
+    fn show_node_state(&self) {
+        let db = self.db.borrow();
+        let Some(db) = &*db else {
+            return;
+        };
+
+        match db.node_state() {
+            Ok(Some(state)) => {
+                let view = &self.db_page_view.node_state;
+                view.show(&state);
+                self.db_page_view.main_view.set_visible_child(view.as_ref());
+            }
+            // Don't leave the dashboard of a previously opened DB
+            Ok(None) => {
+                let page_view = &self.db_page_view;
+                let node_state = page_view.node_state.as_ref().upcast_ref::<gtk::Widget>();
+                if page_view.main_view.visible_child().as_ref() == Some(node_state) {
+                    page_view.main_view.set_visible_child(&page_view.empty_page);
+                }
+            }
+            Err(e) => ui::error_dialog(&self.window, format!("{e:?}")).show_all(),
+        }
+    }
+
+    fn select_page<T: AsRef<gtk::Box>>(&self, page: &T) {
+        self.view_stack.set_visible_child(page.as_ref());
+    }
+
+    fn open_cf(&self, cf_name: &str) {
+        self.reset_filter(cf_name);
+        self.load_cf(cf_name, CfRange::All);
+        self.update_block_navigator(cf_name);
+    }
+
+    /// Filters refer to the fields of one column family, so they are dropped when it changes.
+    fn reset_filter(&self, cf_name: &str) {
+        if self.selected_cf.borrow().as_deref() != Some(cf_name) {
+            self.filter.borrow_mut().take();
+            self.db_page_view.filter_bar.clear();
+        }
+    }
+
+    /// Shows the shard navigator if the keys of the column family start with a block id.
+    fn update_block_navigator(&self, cf_name: &str) {
+        let db = self.db.borrow();
+        let Some(db) = &*db else {
+            return;
+        };
+
+        let shards = self.get_cf_handle(db, cf_name).and_then(|cf_handle| {
+            if cf_handle.key_repr().has_block_id_prefix() {
+                db.find_shards(&cf_handle).map(Some)
+            } else {
+                Ok(None)
+            }
+        });
+
+        let navigator = &self.db_page_view.block_navigator;
+        match shards {
+            Ok(shards) => navigator.set_shards(shards),
+            Err(e) => {
+                navigator.set_shards(None);
+                ui::error_dialog(&self.window, format!("{e:?}")).show_all();
+            }
+        }
+    }
+
+    fn navigate_blocks(&self, workchain: i32, shard: u64, from: &str, to: &str) {
+        let Some(cf_name) = self.selected_cf.borrow().clone() else {
+            return;
+        };
+
+        let parse_seqno = |text: &str, default| match text.trim() {
+            "" => Ok(default),
+            text => text
+                .parse::<u32>()
+                .with_context(|| format!("Invalid seqno: {text}")),
+        };
+        let seqno = parse_seqno(from, 0).and_then(|from| {
+            let to = parse_seqno(to, u32::MAX)?;
+            anyhow::ensure!(from <= to, "Invalid seqno range: {from} > {to}");
+            Ok(from..=to)
+        });
+        let seqno = match seqno {
+            Ok(seqno) => seqno,
+            Err(e) => {
+                ui::error_dialog(&self.window, format!("{e:?}")).show_all();
+                return;
+            }
+        };
+
+        self.db_page_view.set_status_bar_text(format!(
+            "Showing blocks {workchain}:{shard:016x} with seqno {} to {}",
+            seqno.start(),
+            seqno.end()
+        ));
+        self.load_cf(
+            &cf_name,
+            CfRange::ShardBlocks {
+                workchain,
+                shard,
+                seqno,
+            },
+        );
+    }
+
+    fn apply_filter(&self, text: &str) {
+        let Some(cf_name) = self.selected_cf.borrow().clone() else {
+            return;
+        };
+
+        let filter = if text.is_empty() {
+            None
+        } else {
+            match text.parse::<controller::Filter>() {
+                Ok(filter) => Some(Arc::new(filter)),
+                Err(e) => {
+                    self.db_page_view.filter_bar.set_error(Some(&e.to_string()));
+                    return;
+                }
+            }
+        };
+        self.db_page_view.filter_bar.set_error(None);
+
+        *self.filter.borrow_mut() = filter;
+        self.load_cf(&cf_name, CfRange::All);
+    }
+
+    fn seek_cf(&self, text: &str) {
+        let Some(cf_name) = self.selected_cf.borrow().clone() else {
+            return;
+        };
+
+        let key = {
+            let db = self.db.borrow();
+            let Some(db) = &*db else {
+                return;
+            };
+
+            self.get_cf_handle(db, &cf_name)
+                .and_then(|cf_handle| cf_handle.key_repr().encode(text))
+        };
+        let key = match key {
+            Ok(key) => key,
+            Err(e) => {
+                ui::error_dialog(&self.window, format!("{e:?}")).show_all();
+                return;
+            }
+        };
+
+        self.load_cf(&cf_name, CfRange::From(&key));
+        if self.db_page_view.cf_view.select_key(&key) {
+            self.db_page_view
+                .set_status_bar_text(format!("Found key {}", hex::encode(&key)));
+        } else {
+            self.db_page_view.set_status_bar_text(format!(
+                "Key {} not found, showing the following entries",
+                hex::encode(&key)
+            ));
+        }
+    }
+
+    fn multi_get(&self, lines: &[String]) {
+        let db = self.db.borrow();
+        let (Some(db), Some(cf_name)) = (&*db, &*self.selected_cf.borrow()) else {
+            return;
+        };
+
+        let cf_handle = match self.get_cf_handle(db, cf_name) {
+            Ok(handle) => handle,
+            Err(e) => {
+                ui::error_dialog(&self.window, format!("{e:?}")).show_all();
+                return;
+            }
+        };
+        let key_repr = cf_handle.key_repr();
+        let value_repr = cf_handle.value_repr();
+
+        let encoded = lines
+            .iter()
+            .map(|line| key_repr.encode(line))
+            .collect::<Vec<_>>();
+        let keys = encoded
+            .iter()
+            .filter_map(|key| key.as_ref().ok().cloned())
+            .collect::<Vec<_>>();
+
+        let values = match db.multi_get(&cf_handle, &keys) {
+            Ok(values) => values,
+            Err(e) => {
+                ui::error_dialog(&self.window, format!("{e:?}")).show_all();
+                return;
+            }
+        };
+
+        let mut values = keys.iter().zip(values);
+        let results = lines.iter().zip(encoded).map(|(line, key)| {
+            let input = line.clone();
+            if let Err(e) = key {
+                return ui::GetResult {
+                    input,
+                    status: format!("invalid key: {e}"),
+                    key: String::new(),
+                    value: String::new(),
+                };
+            }
+
+            let (key, value) = values.next().unwrap();
+            let decoded_key = key_repr.decode(key, key).to_string();
+            match value {
+                Some(value) => ui::GetResult {
+                    input,
+                    status: "found".to_owned(),
+                    key: decoded_key,
+                    value: value_repr.decode(key, &value).to_string(),
+                },
+                None => ui::GetResult {
+                    input,
+                    status: "not found".to_owned(),
+                    key: decoded_key,
+                    value: String::new(),
+                },
+            }
+        });
+        self.get_dialog.set_results(results);
+    }
+
+    fn start_search(
+        self: &Rc<Self>,
+        mode: controller::SearchMode,
+        target: controller::SearchTarget,
+        pattern: &str,
+        all_cfs: bool,
+    ) {
+        const MAX_MATCHES: usize = 10000;
+
+        self.cancel_search();
+
+        let db = self.db.borrow();
+        let Some(db) = &*db else {
+            return;
+        };
+
+        let cf_names = if all_cfs {
+            db.column_families().to_vec()
+        } else {
+            match &*self.selected_cf.borrow() {
+                Some(cf_name) => vec![cf_name.clone()],
+                None => return,
+            }
+        };
+
+        let res = controller::SearchQuery::new(mode, target, pattern).and_then(|mut query| {
+            if !all_cfs {
+                query.set_filter(self.filter.borrow().clone());
+            }
+            let cf_handles = cf_names
+                .iter()
+                .map(|cf_name| self.get_cf_handle(db, cf_name))
+                .collect::<Result<Vec<_>>>()?;
+            Ok((query, cf_handles))
+        });
+        let (query, cf_handles) = match res {
+            Ok(res) => res,
+            Err(e) => {
+                ui::error_dialog(&self.window, format!("{e:?}")).show_all();
+                return;
+            }
+        };
+
+        let page = &self.db_page_view;
+        if all_cfs {
+            self.search_results.clear();
+            self.search_results.present();
+        } else {
+            page.detail_view.clear();
+            page.cf_view.clear();
+        }
+        page.search_bar.set_running(true);
+        page.search_bar.set_progress(0.0, "0 matches");
+
+        let (sender, receiver) = task_channel();
+        let task = db.search(&cf_handles, query, MAX_MATCHES, move |cf_name, event| {
+            sender.send((cf_name.to_owned(), event))
+        });
+
+        let mut positions = cf_names
+            .into_iter()
+            .map(|cf_name| (cf_name, (0.0, 0)))
+            .collect::<HashMap<String, (f64, usize)>>();
+        let mut running = positions.len();
+        let mut matches = 0;
+        let mut limit_reached = false;
+        self.attach_task(&task, receiver, move |this, (cf_name, event)| {
+            let page = &this.db_page_view;
+            match event {
+                controller::SearchEvent::Match(entry) if all_cfs => {
+                    this.search_results.append(&cf_name, *entry);
+                    matches += 1;
+                    return glib::Continue(true);
+                }
+                controller::SearchEvent::Match(entry) => {
+                    page.cf_view.append(*entry);
+                    matches += 1;
+                    return glib::Continue(true);
+                }
+                controller::SearchEvent::Progress { scanned, position } => {
+                    positions.insert(cf_name, (position, scanned));
+                }
+                controller::SearchEvent::Finished {
+                    scanned,
+                    limit_reached: cf_limit_reached,
+                } => {
+                    positions.insert(cf_name, (1.0, scanned));
+                    limit_reached |= cf_limit_reached;
+                    running -= 1;
+                }
+                controller::SearchEvent::Failed(e) => {
+                    if let Some((position, _)) = positions.get_mut(&cf_name) {
+                        *position = 1.0;
+                    }
+                    running -= 1;
+                    let e = e.context(format!("Search in {cf_name} failed"));
+                    ui::error_dialog(&this.window, format!("{e:?}")).show_all();
+                }
+            }
+
+            let position = positions
+                .values()
+                .map(|(position, _)| position)
+                .sum::<f64>()
+                / positions.len() as f64;
+            let scanned = positions
+                .values()
+                .map(|(_, scanned)| scanned)
+                .sum::<usize>();
+            if running > 0 {
+                page.search_bar.set_progress(
+                    position,
+                    &format!("{matches} matches, {scanned} keys scanned"),
+                );
+                return glib::Continue(true);
+            }
+
+            this.search.borrow_mut().take();
+            page.search_bar.set_running(false);
+            page.search_bar
+                .set_progress(1.0, &format!("{matches} matches"));
+            let mut text = format!("Search finished: {matches} matches, {scanned} keys scanned");
+            if limit_reached {
+                text += &format!(" (stopped at {MAX_MATCHES} matches per column family)");
+            }
+            page.set_status_bar_text(text);
+            glib::Continue(false)
+        });
+
+        *self.search.borrow_mut() = Some(RunningSearch { task, all_cfs });
+    }
+
+    fn cancel_search(&self) {
+        if let Some(running) = self.search.borrow_mut().take() {
+            running.task.cancel();
+        }
+        self.db_page_view.search_bar.set_running(false);
+    }
+
+    /// Runs an integrity check in the background, listing problems as they are found.
+    fn start_verify(self: &Rc<Self>, check: controller::IntegrityCheck) {
+        self.cancel_verify();
+
+        let db = self.db.borrow();
+        let Some(db) = &*db else {
+            return;
+        };
+
+        let dialog = &self.integrity_results;
+        dialog.set_title(check.title());
+        dialog.set_note(check.limitations());
+        dialog.clear();
+        dialog.set_running(true);
+        dialog.set_progress(0.0, "0 problems");
+        dialog.present();
+
+        let (sender, receiver) = task_channel();
+        let task = db.verify(check, move |event| sender.send(event));
+
+        let mut issues = 0;
+        self.attach_task(&task, receiver, move |this, event| {
+            let dialog = &this.integrity_results;
+            let scanned = match event {
+                controller::IntegrityEvent::Issue(issue) => {
+                    dialog.append(*issue);
+                    issues += 1;
+                    return glib::Continue(true);
+                }
+                controller::IntegrityEvent::Progress { scanned, position } => {
+                    dialog.set_progress(
+                        position,
+                        &format!("{issues} problems, {scanned} entries checked"),
+                    );
+                    return glib::Continue(true);
+                }
+                controller::IntegrityEvent::Finished { scanned } => scanned,
+                controller::IntegrityEvent::Failed(e) => {
+                    let e = e.context(format!("{} failed", check.title()));
+                    ui::error_dialog(&this.window, format!("{e:?}")).show_all();
+                    0
+                }
+            };
+
+            this.verify.borrow_mut().take();
+            dialog.set_running(false);
+            dialog.set_progress(
+                1.0,
+                &format!("{issues} problems, {scanned} entries checked"),
+            );
+            glib::Continue(false)
+        });
+
+        *self.verify.borrow_mut() = Some(task);
+    }
+
+    fn cancel_verify(&self) {
+        if let Some(task) = self.verify.borrow_mut().take() {
+            task.cancel();
+        }
+        self.integrity_results.set_running(false);
+    }
+
+    fn show_archive_coverage(&self) {
+        if self.db.borrow().is_some() {
+            let coverage = &self.db_page_view.archive_coverage;
+            self.db_page_view
+                .main_view
+                .set_visible_child(coverage.as_ref());
+        }
+    }
+
+    /// Decodes all archives in the background, listing their ranges as they are scanned.
+    fn start_coverage(self: &Rc<Self>) {
+        self.cancel_coverage();
+
+        let db = self.db.borrow();
+        let Some(db) = &*db else {
+            return;
+        };
+
+        let view = &self.db_page_view.archive_coverage;
+        view.clear();
+        view.set_running(true);
+
+        let (sender, receiver) = task_channel();
+        let task = db.archive_coverage(move |event| sender.send(event));
+
+        let mut issues = 0;
+        self.attach_task(&task, receiver, move |this, event| {
+            let view = &this.db_page_view.archive_coverage;
+            let scanned = match event {
+                controller::CoverageEvent::Archive { archive, position } => {
+                    let continuous = matches!(
+                        archive.continuity,
+                        controller::Continuity::First | controller::Continuity::Continuous
+                    );
+                    if !continuous || archive.error.is_some() {
+                        issues += 1;
+                    }
+                    view.append(*archive);
+                    view.set_progress(position, &format!("{issues} problems"));
+                    return glib::Continue(true);
+                }
+                controller::CoverageEvent::Finished { scanned } => scanned,
+                controller::CoverageEvent::Failed(e) => {
+                    let e = e.context("Archive scan failed");
+                    ui::error_dialog(&this.window, format!("{e:?}")).show_all();
+                    0
+                }
+            };
+
+            this.coverage.borrow_mut().take();
+            view.set_running(false);
+            view.set_progress(
+                1.0,
+                &format!("{issues} problems, {scanned} archives scanned"),
+            );
+            glib::Continue(false)
+        });
+
+        *self.coverage.borrow_mut() = Some(task);
+    }
+
+    fn cancel_coverage(&self) {
+        if let Some(task) = self.coverage.borrow_mut().take() {
+            task.cancel();
+        }
+        self.db_page_view.archive_coverage.set_running(false);
+    }
+
+    fn show_key_blocks(&self) {
+        if self.db.borrow().is_some() {
+            let key_blocks = &self.db_page_view.key_blocks;
+            self.db_page_view
+                .main_view
+                .set_visible_child(key_blocks.as_ref());
+        }
+    }
+
+    /// Lists the key blocks in the background, decoding the stored ones.
+    fn start_key_blocks(self: &Rc<Self>) {
+        self.cancel_key_blocks();
+
+        let db = self.db.borrow();
+        let Some(db) = &*db else {
+            return;
+        };
+
+        let view = &self.db_page_view.key_blocks;
+        view.clear();
+        view.set_running(true);
+
+        let (sender, receiver) = task_channel();
+        let task = db.key_blocks(move |event| sender.send(event));
+
+        let mut config_changes = 0;
+        self.attach_task(&task, receiver, move |this, event| {
+            let view = &this.db_page_view.key_blocks;
+            let scanned = match event {
+                controller::KeyBlockEvent::Block {
+                    key_block,
+                    position,
+                } => {
+                    if let controller::ConfigChange::Changed(_) = key_block.config {
+                        config_changes += 1;
+                    }
+                    view.append(*key_block);
+                    view.set_progress(position, &format!("{config_changes} config changes"));
+                    return glib::Continue(true);
+                }
+                controller::KeyBlockEvent::Finished { scanned } => scanned,
+                controller::KeyBlockEvent::Failed(e) => {
+                    let e = e.context("Failed to list key blocks");
+                    ui::error_dialog(&this.window, format!("{e:?}")).show_all();
+                    0
+                }
+            };
 
-        shared_state.welcome_page_view.open_btn.connect_clicked(
-            glib::clone!(@strong folder_dialog => move |_| {
-                folder_dialog.show_all();
-            }),
-        );
+            this.key_blocks.borrow_mut().take();
+            view.set_running(false);
+            view.set_progress(
+                1.0,
+                &format!("{config_changes} config changes, {scanned} key blocks"),
+            );
+            glib::Continue(false)
+        });
 
-        shared_state.db_page_view.cf_list.connect_cf_selected(
-            glib::clone!(@weak shared_state => move |cf_name| {
-                shared_state.open_cf(cf_name);
-            }),
-        );
+        *self.key_blocks.borrow_mut() = Some(task);
+    }
 
-        shared_state.window.show_all();
+    fn cancel_key_blocks(&self) {
+        if let Some(task) = self.key_blocks.borrow_mut().take() {
+            task.cancel();
+        }
+        self.db_page_view.key_blocks.set_running(false);
+    }
 
-        if let Some(path) = initial_path {
-            shared_state.open_db(path);
+    fn show_block_explorer(&self) {
+        if self.db.borrow().is_some() {
+            let explorer = &self.db_page_view.block_explorer;
+            self.db_page_view
+                .main_view
+                .set_visible_child(explorer.as_ref());
         }
-        Self(shared_state)
     }
-}
 
-impl AsRef<ApplicationWindow> for Window {
-    fn as_ref(&self) -> &ApplicationWindow {
-        &self.0.window
+    fn explore_block(self: &Rc<Self>, text: &str) {
+        self.cancel_explore();
+        let db = self.db.borrow();
+        let Some(db) = &*db else {
+            return;
+        };
+
+        let explorer = &self.db_page_view.block_explorer;
+        explorer.set_block_text(text);
+        self.db_page_view
+            .main_view
+            .set_visible_child(explorer.as_ref());
+
+        explorer.clear();
+
+        let (sender, receiver) = task_channel();
+        let task = db.explore_block(text, move |event| sender.send(event));
+        self.attach_task(&task, receiver, move |this, event| {
+            let explorer = &this.db_page_view.block_explorer;
+            match event {
+                controller::ExploreEvent::Block { id, items } => {
+                    explorer.show(&id, items);
+                    return glib::Continue(true);
+                }
+                controller::ExploreEvent::Archive(Some(item)) => explorer.append(item),
+                controller::ExploreEvent::Archive(None) => {}
+                controller::ExploreEvent::Failed(e) => {
+                    ui::error_dialog(&this.window, format!("{e:?}")).show_all();
+                }
+            }
+            this.explore.borrow_mut().take();
+            glib::Continue(false)
+        });
+        *self.explore.borrow_mut() = Some(task);
     }
-}
 
-struct WindowState {
-    window: ApplicationWindow,
-    db: RefCell<Option<controller::Db>>,
-    view_stack: gtk::Stack,
-    welcome_page_view: WelcomePageView,
-    db_page_view: DbPageView,
-}
+    fn cancel_explore(&self) {
+        if let Some(task) = self.explore.borrow_mut().take() {
+            task.cancel();
+        }
+    }
 
-impl WindowState {
-    fn open_db(&self, path: PathBuf) {
-        let opened_db = match controller::Db::open(path) {
-            Ok(db) => db,
-            Err(e) => {
-                ui::error_dialog(&self.window, format!("{e:?}")).show_all();
-                return;
+    fn show_chain_graph_page(&self) {
+        if self.db.borrow().is_some() {
+            let chain_graph = &self.db_page_view.chain_graph;
+            self.db_page_view
+                .main_view
+                .set_visible_child(chain_graph.as_ref());
+        }
+    }
+
+    fn show_chain_graph(self: &Rc<Self>, text: &str, depth: u32) {
+        self.cancel_chain_graph();
+        let db = self.db.borrow();
+        let Some(db) = &*db else {
+            return;
+        };
+
+        let chain_graph = &self.db_page_view.chain_graph;
+        chain_graph.set_block_text(text);
+        chain_graph.clear();
+        self.db_page_view
+            .main_view
+            .set_visible_child(chain_graph.as_ref());
+
+        let (sender, receiver) = task_channel();
+        let task = db.chain_graph(text, depth, move |res| sender.send(res));
+        self.attach_task(&task, receiver, move |this, res| {
+            this.chain_graph.borrow_mut().take();
+            match res {
+                Ok(graph) => this.db_page_view.chain_graph.show(graph),
+                Err(e) => ui::error_dialog(&this.window, format!("{e:?}")).show_all(),
             }
+            glib::Continue(false)
+        });
+        *self.chain_graph.borrow_mut() = Some(task);
+    }
+
+    fn cancel_chain_graph(&self) {
+        if let Some(task) = self.chain_graph.borrow_mut().take() {
+            task.cancel();
+        }
+    }
+
+    /// Explores the block whose id is the key or the value of the selected row.
+    fn explore_selected_block(self: &Rc<Self>) {
+        let rows = self.db_page_view.cf_view.selected_rows();
+        let [row] = rows.as_slice() else {
+            return;
         };
 
-        let mut db = self.db.borrow_mut();
-        let db = db.insert(opened_db);
+        let block_id = {
+            let db = self.db.borrow();
+            let (Some(db), Some(cf_name)) = (&*db, &*self.selected_cf.borrow()) else {
+                return;
+            };
+            let cf_handle = match self.get_cf_handle(db, cf_name) {
+                Ok(handle) => handle,
+                Err(e) => {
+                    ui::error_dialog(&self.window, format!("{e:?}")).show_all();
+                    return;
+                }
+            };
 
-        self.db_page_view.init_for_db(db);
-        self.select_page(&self.db_page_view);
+            // Values too large for the table are never block ids
+            let value = row.value.as_deref().unwrap_or_default();
+            let key_repr = cf_handle.key_repr();
+            let value_repr = cf_handle.value_repr();
+            key_repr
+                .decode(&row.key, &row.key)
+                .block_id_text()
+                .or_else(|| value_repr.decode(&row.key, value).block_id_text())
+        };
+
+        match block_id {
+            Some(block_id) => self.explore_block(&block_id),
+            None => self
+                .db_page_view
+                .set_status_bar_text("The selected row has no block id"),
+        }
     }
 
-    fn select_page<T: AsRef<gtk::Box>>(&self, page: &T) {
-        self.view_stack.set_visible_child(page.as_ref());
+    fn open_search_hit(&self, cf_name: &str, key: &[u8]) {
+        self.push_history();
+        self.show_location(&Location {
+            cf_name: cf_name.to_owned(),
+            key: Some(key.to_vec()),
+        });
     }
 
-    fn open_cf(&self, cf_name: &str) {
-        let db = self.db.borrow_mut();
+    /// Opens the row which the block id links to in the column family chosen in the toolbar.
+    fn follow_link(&self, block_id: &controller::BlockIdFull) {
+        let target = self.db_page_view.cf_toolbar.link_target();
+        self.open_search_hit(target.cf_name(), &target.key(block_id));
+    }
+
+    fn current_location(&self) -> Option<Location> {
+        let cf_name = self.selected_cf.borrow().clone()?;
+        let key = match self.db_page_view.cf_view.selected_rows().as_slice() {
+            [row] => Some(row.key.clone()),
+            _ => None,
+        };
+        Some(Location { cf_name, key })
+    }
+
+    /// Remembers the current location before leaving it.
+    fn push_history(&self) {
+        if let Some(location) = self.current_location() {
+            let mut history = self.history.borrow_mut();
+            history.back.push(location);
+            history.forward.clear();
+        }
+        self.update_history_buttons();
+    }
+
+    fn go_back(&self) {
+        let Some(location) = self.history.borrow_mut().back.pop() else {
+            return;
+        };
+        if let Some(current) = self.current_location() {
+            self.history.borrow_mut().forward.push(current);
+        }
+        self.show_location(&location);
+        self.update_history_buttons();
+    }
+
+    fn go_forward(&self) {
+        let Some(location) = self.history.borrow_mut().forward.pop() else {
+            return;
+        };
+        if let Some(current) = self.current_location() {
+            self.history.borrow_mut().back.push(current);
+        }
+        self.show_location(&location);
+        self.update_history_buttons();
+    }
+
+    fn update_history_buttons(&self) {
+        let history = self.history.borrow();
+        let toolbar = &self.db_page_view.cf_toolbar;
+        toolbar.back_btn.set_sensitive(!history.back.is_empty());
+        toolbar
+            .forward_btn
+            .set_sensitive(!history.forward.is_empty());
+    }
+
+    fn show_location(&self, location: &Location) {
+        let cf_name = &location.cf_name;
+        self.db_page_view.cf_list.select_cf(cf_name);
+        let Some(key) = &location.key else {
+            self.open_cf(cf_name);
+            return;
+        };
+
+        self.reset_filter(cf_name);
+        self.load_cf(cf_name, CfRange::From(key));
+        self.update_block_navigator(cf_name);
+        if !self.db_page_view.cf_view.select_key(key) {
+            self.db_page_view.set_status_bar_text(format!(
+                "Key {} not found in {cf_name}, showing the following rows",
+                hex::encode(key)
+            ));
+        }
+    }
+
+    fn load_cf(&self, cf_name: &str, range: CfRange<'_>) {
+        const MAX_ROWS: usize = 10000;
+        // Keeps a filter without matches from blocking the UI on a large column family
+        const MAX_FILTERED_ROWS: usize = 100000;
+
+        // Matches of a global search don't live in the table
+        let cf_search = matches!(&*self.search.borrow(), Some(running) if !running.all_cfs);
+        if cf_search {
+            self.cancel_search();
+        }
+
+        let db = self.db.borrow();
         let Some(db) = &*db else {
             return;
         };
 
-        let cf_handle = match db.get_cf_handle(cf_name) {
+        let cf_handle = match self.get_cf_handle(db, cf_name) {
             Ok(handle) => handle,
             Err(e) => {
-                ui::error_dialog(&self.window, e).show_all();
+                ui::error_dialog(&self.window, format!("{e:?}")).show_all();
                 return;
             }
         };
-        let iter = db.iter(cf_handle).take(10000);
-        self.db_page_view.cf_view.update(iter);
+        *self.selected_cf.borrow_mut() = Some(cf_name.to_owned());
+
+        self.db_page_view.detail_view.clear();
+        self.db_page_view
+            .cf_view
+            .set_reprs(cf_handle.key_repr(), cf_handle.value_repr());
+        self.db_page_view
+            .filter_bar
+            .set_presets(cf_handle.value_repr().filter_presets());
+        let iter = match range {
+            CfRange::All => db.iter(cf_handle),
+            CfRange::From(key) => db.iter_from(cf_handle, key),
+            CfRange::ShardBlocks {
+                workchain,
+                shard,
+                seqno,
+            } => db.iter_shard_blocks(cf_handle, workchain, shard, seqno),
+        };
+        let filter = self.filter.borrow();
+        let mut scanned = 0;
+        let iter = iter
+            .take_while(|_| {
+                scanned += 1;
+                filter.is_none() || scanned <= MAX_FILTERED_ROWS
+            })
+            .filter(|entry| match &*filter {
+                Some(filter) => filter.matches(entry),
+                None => true,
+            });
+        self.db_page_view.cf_view.update(iter.take(MAX_ROWS));
+        if scanned > MAX_FILTERED_ROWS {
+            self.db_page_view.set_status_bar_text(format!(
+                "Filter stopped after {MAX_FILTERED_ROWS} rows, search the column family to check all of them"
+            ));
+        }
         self.db_page_view
             .main_view
             .set_visible_child(&self.db_page_view.table_page);
     }
+
+    /// Opens a column family handle with the decoders chosen for it.
+    fn get_cf_handle<'a>(
+        &self,
+        db: &'a controller::Db,
+        cf_name: &str,
+    ) -> Result<controller::CfHandle<'a>> {
+        let mut cf_handle = db.get_cf_handle(cf_name)?;
+        if let Some((key_repr, value_repr)) = self.decoder_settings.borrow().get(db.path(), cf_name)
+        {
+            cf_handle.set_reprs(key_repr, value_repr);
+        }
+        Ok(cf_handle)
+    }
+
+    fn show_entry_details(&self, key: Option<&[u8]>) {
+        let detail_view = &self.db_page_view.detail_view;
+
+        let db = self.db.borrow();
+        let (Some(db), Some(cf_name), Some(key)) = (&*db, &*self.selected_cf.borrow(), key) else {
+            detail_view.clear();
+            return;
+        };
+
+        let res = self
+            .get_cf_handle(db, cf_name)
+            .and_then(|cf_handle| Ok((db.get(&cf_handle, key)?, cf_handle)));
+        match res {
+            Ok((value, cf_handle)) => {
+                let value = value.as_deref();
+                let detail_repr = cf_handle.detail_repr();
+
+                // The BOC is parsed once for both the decoded value and the cell tree
+                let boc = match (value, detail_repr) {
+                    (Some(value), controller::ReprKind::Boc) => Some(controller::Boc::parse(value)),
+                    _ => None,
+                };
+                let decoded_value = value.map(|value| match &boc {
+                    Some(Ok(boc)) => boc.summary(value),
+                    Some(Err((offset, reason))) => {
+                        controller::Value::error(0..value.len(), *offset, *reason)
+                    }
+                    None => detail_repr.decode(key, value),
+                });
+
+                detail_view.show(
+                    key,
+                    &cf_handle.key_repr().decode(key, key),
+                    value.zip(decoded_value.as_ref()),
+                );
+
+                if let Some(Ok(boc)) = boc {
+                    let roots = boc.root_hashes();
+                    detail_view.show_cells(Rc::new(boc), &roots);
+                }
+
+                let value = value.unwrap_or_default();
+                let stored_root = match detail_repr {
+                    // Shard states are walked from the state root in the cell storage
+                    controller::ReprKind::ShardState => value.get(..32),
+                    controller::ReprKind::StoredCell => Some(key),
+                    _ => None,
+                };
+
+                let stored_root = stored_root.and_then(|root| <[u8; 32]>::try_from(root).ok());
+                if let (Some(root), Some(storage)) = (stored_root, db.cell_storage()) {
+                    detail_view.show_cells(Rc::new(storage), &[root]);
+                }
+            }
+            Err(e) => {
+                detail_view.clear();
+                ui::error_dialog(&self.window, format!("{e:?}")).show_all();
+            }
+        }
+    }
+
+    fn copy_selection(&self, content: ui::CopyContent) {
+        let cf_view = &self.db_page_view.cf_view;
+        let rows = cf_view.selected_rows();
+        if rows.is_empty() {
+            return;
+        }
+
+        let text = match content {
+            ui::CopyContent::Rows => cf_view.selected_rows_tsv(),
+            ui::CopyContent::Key(format) => rows
+                .iter()
+                .map(|row| format.format(&row.key, &row.decoded_key))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            ui::CopyContent::Value(format) => {
+                let res = rows
+                    .iter()
+                    .map(|row| match &row.value {
+                        _ if !format.is_raw() => Ok(row.decoded_value.clone()),
+                        Some(value) => Ok(format.format(value, &row.decoded_value)),
+                        None => {
+                            let value = self.read_value(&row.key)?.unwrap_or_default();
+                            Ok(format.format(&value, &row.decoded_value))
+                        }
+                    })
+                    .collect::<Result<Vec<_>>>();
+                match res {
+                    Ok(values) => values.join("\n"),
+                    Err(e) => {
+                        ui::error_dialog(&self.window, format!("{e:?}")).show_all();
+                        return;
+                    }
+                }
+            }
+        };
+
+        ui::set_clipboard_text(&text);
+        self.db_page_view
+            .set_status_bar_text(format!("Copied {} row(s)", rows.len()));
+    }
+
+    fn save_value(self: &Rc<Self>) {
+        let Some(cf_name) = self.selected_cf.borrow().clone() else {
+            return;
+        };
+        let rows = self.db_page_view.cf_view.selected_rows();
+        let [row] = rows.as_slice() else {
+            return;
+        };
+
+        let extension = {
+            let db = self.db.borrow();
+            let Some(db) = &*db else {
+                return;
+            };
+            match self.get_cf_handle(db, &cf_name) {
+                Ok(cf_handle)
+                    if cf_handle.detail_repr() == controller::ReprKind::ArchivePackage =>
+                {
+                    "pack"
+                }
+                _ => "bin",
+            }
+        };
+
+        let dialog = ui::file_dialog(&self.window, "Save value", gtk::FileChooserAction::Save);
+        dialog.set_current_name(&format!("{cf_name}_{}.{extension}", hex::encode(&row.key)));
+        let path = match dialog.run() {
+            gtk::ResponseType::Ok => dialog.filename(),
+            _ => None,
+        };
+        dialog.close();
+        let Some(path) = path else {
+            return;
+        };
+
+        self.cancel_transfer();
+        let db = self.db.borrow();
+        let Some(db) = &*db else {
+            return;
+        };
+
+        let (sender, receiver) = task_channel();
+        let res = self.get_cf_handle(db, &cf_name).map(|cf_handle| {
+            db.save_value(&cf_handle, &row.key, path.clone(), move |event| {
+                sender.send(event)
+            })
+        });
+        match res {
+            Ok(task) => self.attach_transfer(task, receiver, "Saving value", move |this, len| {
+                this.db_page_view
+                    .set_status_bar_text(format!("Saved {len} bytes to {}", path.display()));
+            }),
+            Err(e) => ui::error_dialog(&self.window, format!("{e:?}")).show_all(),
+        }
+    }
+
+    /// Saves a part of the value shown in the detail pane.
+    fn save_bytes(&self, name: &str, data: &[u8]) {
+        let dialog = ui::file_dialog(&self.window, "Save bytes", gtk::FileChooserAction::Save);
+        dialog.set_current_name(&name.replace('/', "_"));
+        let path = match dialog.run() {
+            gtk::ResponseType::Ok => dialog.filename(),
+            _ => None,
+        };
+        dialog.close();
+        let Some(path) = path else {
+            return;
+        };
+
+        match std::fs::write(&path, data).context("Failed to write file") {
+            Ok(()) => self.db_page_view.set_status_bar_text(format!(
+                "Saved {} bytes to {}",
+                data.len(),
+                path.display()
+            )),
+            Err(e) => ui::error_dialog(&self.window, format!("{e:?}")).show_all(),
+        }
+    }
+
+    fn replace_value(self: &Rc<Self>) {
+        let Some(cf_name) = self.selected_cf.borrow().clone() else {
+            return;
+        };
+        let rows = self.db_page_view.cf_view.selected_rows();
+        let [row] = rows.as_slice() else {
+            return;
+        };
+
+        let dialog = ui::file_dialog(&self.window, "Replace value", gtk::FileChooserAction::Open);
+        let path = match dialog.run() {
+            gtk::ResponseType::Ok => dialog.filename(),
+            _ => None,
+        };
+        dialog.close();
+        let Some(path) = path else {
+            return;
+        };
+
+        let confirm = ui::confirm_dialog(
+            &self.window,
+            format!(
+                "Replace the value of {} in {cf_name} with the contents of {}?",
+                row.decoded_key,
+                path.display()
+            ),
+        );
+        let confirmed = confirm.run() == gtk::ResponseType::Ok;
+        confirm.close();
+        if !confirmed {
+            return;
+        }
+
+        self.cancel_transfer();
+        let db = self.db.borrow();
+        let Some(db) = &*db else {
+            return;
+        };
+
+        let (sender, receiver) = task_channel();
+        let res = self.get_cf_handle(db, &cf_name).and_then(|cf_handle| {
+            db.replace_value(&cf_handle, &row.key, path, move |event| sender.send(event))
+        });
+        let key = row.key.clone();
+        match res {
+            Ok(task) => {
+                self.attach_transfer(task, receiver, "Reading file", move |this, len| {
+                    // The table may show another column family by now
+                    if this.selected_cf.borrow().as_deref() == Some(cf_name.as_str()) {
+                        this.load_cf(&cf_name, CfRange::From(&key));
+                        this.db_page_view.cf_view.select_key(&key);
+                    }
+                    this.db_page_view
+                        .set_status_bar_text(format!("Replaced value with {len} bytes"));
+                })
+            }
+            Err(e) => ui::error_dialog(&self.window, format!("{e:?}")).show_all(),
+        }
+    }
+
+    /// Passes the events of a background task to `on_event` until it returns
+    /// `Continue(false)`, the task is cancelled or the window is closed.
+    fn attach_task<T, F>(
+        self: &Rc<Self>,
+        task: &controller::Task,
+        receiver: glib::Receiver<T>,
+        mut on_event: F,
+    ) where
+        T: 'static,
+        F: FnMut(&Rc<Self>, T) -> glib::Continue + 'static,
+    {
+        let this = Rc::downgrade(self);
+        let task = task.clone();
+        receiver.attach(None, move |event| {
+            let Some(this) = this.upgrade() else {
+                return glib::Continue(false);
+            };
+            if task.is_cancelled() {
+                return glib::Continue(false);
+            }
+            on_event(&this, event)
+        });
+    }
+
+    /// Shows the progress of a save or replace in the status bar.
+    fn attach_transfer<F>(
+        self: &Rc<Self>,
+        task: controller::Task,
+        receiver: glib::Receiver<controller::TransferEvent>,
+        action: &'static str,
+        on_finished: F,
+    ) where
+        F: FnOnce(&Self, usize) + 'static,
+    {
+        let mut on_finished = Some(on_finished);
+        self.attach_task(&task, receiver, move |this, event| {
+            match event {
+                controller::TransferEvent::Progress { done, total } => {
+                    this.db_page_view
+                        .set_status_bar_text(format!("{action}: {done} of {total} bytes"));
+                    return glib::Continue(true);
+                }
+                controller::TransferEvent::Finished { len: Some(len) } => {
+                    this.transfer.borrow_mut().take();
+                    if let Some(on_finished) = on_finished.take() {
+                        on_finished(this, len);
+                    }
+                }
+                controller::TransferEvent::Finished { len: None } => {
+                    this.transfer.borrow_mut().take();
+                }
+                controller::TransferEvent::Failed(e) => {
+                    this.transfer.borrow_mut().take();
+                    ui::error_dialog(&this.window, format!("{e:?}")).show_all();
+                }
+            }
+            glib::Continue(false)
+        });
+        *self.transfer.borrow_mut() = Some(task);
+    }
+
+    fn cancel_transfer(&self) {
+        if let Some(task) = self.transfer.borrow_mut().take() {
+            task.cancel();
+        }
+    }
+
+    /// Reads a value of the selected column family that is not kept in the table.
+    fn read_value(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let db = self.db.borrow();
+        let (Some(db), Some(cf_name)) = (&*db, &*self.selected_cf.borrow()) else {
+            anyhow::bail!("No column family selected");
+        };
+        let cf_handle = self.get_cf_handle(db, cf_name)?;
+        db.get(&cf_handle, key)
+    }
+
+    fn set_cf_repr(&self, column: ui::ReprColumn, repr: controller::ReprKind) {
+        let Some(cf_name) = self.selected_cf.borrow().clone() else {
+            return;
+        };
+
+        {
+            let db = self.db.borrow();
+            let Some(db) = &*db else {
+                return;
+            };
+
+            let (mut key_repr, mut value_repr) = match self.get_cf_handle(db, &cf_name) {
+                Ok(handle) => (handle.key_repr(), handle.value_repr()),
+                Err(e) => {
+                    ui::error_dialog(&self.window, format!("{e:?}")).show_all();
+                    return;
+                }
+            };
+            match column {
+                ui::ReprColumn::Key => key_repr = repr,
+                ui::ReprColumn::Value => value_repr = repr,
+            }
+
+            let res =
+                self.decoder_settings
+                    .borrow_mut()
+                    .set(db.path(), &cf_name, key_repr, value_repr);
+            if let Err(e) = res {
+                ui::error_dialog(&self.window, format!("{e:?}")).show_all();
+            }
+        }
+
+        self.open_cf(&cf_name);
+    }
+}
+
+/// Part of a column family shown in the table.
+enum CfRange<'a> {
+    All,
+    From(&'a [u8]),
+    ShardBlocks {
+        workchain: i32,
+        shard: u64,
+        seqno: RangeInclusive<u32>,
+    },
+}
+
+/// Sends the events of a background task to the window.
+struct TaskSender<T>(glib::Sender<T>);
+
+impl<T> TaskSender<T> {
+    fn send(&self, event: T) {
+        // The receiver is gone once the task is cancelled
+        self.0.send(event).ok();
+    }
+}
+
+impl<T> Clone for TaskSender<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+fn task_channel<T>() -> (TaskSender<T>, glib::Receiver<T>) {
+    let (sender, receiver) = glib::MainContext::channel(glib::Priority::default());
+    (TaskSender(sender), receiver)
+}
+
+struct RunningSearch {
+    task: controller::Task,
+    all_cfs: bool,
+}
+
+/// Table position returned to with the back and forward buttons.
+struct Location {
+    cf_name: String,
+    /// Selected row, if there was exactly one.
+    key: Option<Vec<u8>>,
+}
+
+#[derive(Default)]
+struct History {
+    back: Vec<Location>,
+    forward: Vec<Location>,
 }
 
 struct WelcomePageView {
@@ -182,16 +1725,39 @@ struct DbPageView {
     container: gtk::Box,
     status_bar: gtk::Statusbar,
     cf_list: ui::CfList,
+    cf_toolbar: ui::CfToolbar,
+    block_navigator: ui::BlockNavigator,
+    filter_bar: ui::FilterBar,
+    search_bar: ui::SearchBar,
     cf_view: ui::CfView,
+    detail_view: ui::DetailView,
+    block_explorer: ui::BlockExplorer,
+    chain_graph: ui::ChainGraphView,
+    archive_coverage: ui::ArchiveCoverageView,
+    key_blocks: ui::KeyBlocksView,
+    node_state: ui::NodeStateView,
     main_view: gtk::Stack,
     empty_page: gtk::Box,
-    table_page: gtk::ScrolledWindow,
+    table_page: gtk::Box,
 }
 
 impl DbPageView {
     fn new() -> Self {
         let cf_list = ui::CfList::new();
+        let cf_toolbar = ui::CfToolbar::new();
+        let block_navigator = ui::BlockNavigator::new();
+        let filter_bar = ui::FilterBar::new();
+        let search_bar = ui::SearchBar::new();
         let cf_view = ui::CfView::new();
+        let detail_view = ui::DetailView::new();
+        let block_explorer = ui::BlockExplorer::new();
+        let chain_graph = ui::ChainGraphView::new();
+        let archive_coverage = ui::ArchiveCoverageView::new();
+        let key_blocks = ui::KeyBlocksView::new();
+        let node_state = ui::NodeStateView::new();
+        cf_toolbar
+            .columns_btn
+            .set_popup(Some(&cf_view.columns_menu));
 
         let paned = gtk::Paned::new(gtk::Orientation::Horizontal);
 
@@ -206,10 +1772,27 @@ impl DbPageView {
         empty_page.pack_start(&label, true, true, 0);
         main_view.add(&empty_page);
 
-        let table_page = gtk::ScrolledWindow::new(gtk::Adjustment::NONE, gtk::Adjustment::NONE);
-        table_page.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Always);
-        table_page.add(cf_view.as_ref());
+        let table_window = gtk::ScrolledWindow::new(gtk::Adjustment::NONE, gtk::Adjustment::NONE);
+        table_window.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Always);
+        table_window.add(cf_view.as_ref());
+
+        let table_paned = gtk::Paned::new(gtk::Orientation::Vertical);
+        table_paned.pack1(&table_window, true, false);
+        table_paned.pack2(detail_view.as_ref(), false, false);
+        table_paned.set_position(450);
+
+        let table_page = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        table_page.pack_start(cf_toolbar.as_ref(), false, false, 0);
+        table_page.pack_start(block_navigator.as_ref(), false, false, 0);
+        table_page.pack_start(filter_bar.as_ref(), false, false, 0);
+        table_page.pack_start(search_bar.as_ref(), false, false, 0);
+        table_page.pack_start(&table_paned, true, true, 0);
         main_view.add(&table_page);
+        main_view.add(block_explorer.as_ref());
+        main_view.add(chain_graph.as_ref());
+        main_view.add(archive_coverage.as_ref());
+        main_view.add(key_blocks.as_ref());
+        main_view.add(node_state.as_ref());
 
         paned.add2(&main_view);
 
@@ -223,7 +1806,17 @@ impl DbPageView {
             container,
             status_bar,
             cf_list,
+            cf_toolbar,
+            block_navigator,
+            filter_bar,
+            search_bar,
             cf_view,
+            detail_view,
+            block_explorer,
+            chain_graph,
+            archive_coverage,
+            key_blocks,
+            node_state,
             main_view,
             empty_page,
             table_page,
@@ -232,7 +1825,14 @@ impl DbPageView {
 
     fn init_for_db(&self, db: &controller::Db) {
         self.cf_list.update_cfs(db.column_families());
-        self.set_status_bar_text(format!("Opened DB: {}", db.path().display()));
+        self.cf_view.set_writable(db.is_writable());
+        self.block_explorer.clear();
+        self.chain_graph.clear();
+        self.archive_coverage.clear();
+        self.key_blocks.clear();
+
+        let mode = if db.is_writable() { "" } else { " (read-only)" };
+        self.set_status_bar_text(format!("Opened DB{mode}: {}", db.path().display()));
     }
 
     fn set_status_bar_text<T: AsRef<str>>(&self, text: T) {